@@ -0,0 +1,62 @@
+//! Compares the ping-pong, incremental and bitboard backends of `Day4Solver` on a large grid that
+//! needs many generations to become stable.
+//! Run with `cargo bench --bench day4_backends`.
+
+use advent_of_code_2025::day4::solver::{Backend, Day4Solver};
+use advent_of_code_2025::rng::XorShift;
+use std::time::Instant;
+
+const SIZE: usize = 1000;
+const DENSITY_PERCENT: u64 = 65;
+
+fn random_grid(size: usize, density_percent: u64) -> Vec<String> {
+    let mut rng = XorShift::default();
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    if rng.below(100) < density_percent {
+                        '@'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn run(backend: Backend, grid: &[String]) -> (Vec<u64>, f64) {
+    let mut solver = Day4Solver::default().with_backend(backend);
+    for line in grid {
+        solver.add_row(line).expect("Failed to add row");
+    }
+    solver.finalize_input().expect("Empty grid");
+
+    let start = Instant::now();
+    let history = solver.run_until_stable(None).expect("Solver failed");
+    let elapsed = start.elapsed().as_secs_f64();
+
+    (history.iter().map(|g| g.count()).collect(), elapsed)
+}
+
+fn main() {
+    let grid = random_grid(SIZE, DENSITY_PERCENT);
+
+    let (ping_pong, ping_pong_time) = run(Backend::PingPong, &grid);
+    let (incremental, incremental_time) = run(Backend::Incremental, &grid);
+    let (bitboard, bitboard_time) = run(Backend::Bitboard, &grid);
+    assert_eq!(ping_pong, incremental, "Backends disagree");
+    assert_eq!(ping_pong, bitboard, "Backends disagree");
+
+    println!(
+        "{}x{} grid, {} generations, {} crates removed",
+        SIZE,
+        SIZE,
+        ping_pong.len(),
+        ping_pong.iter().sum::<u64>()
+    );
+    println!("ping-pong:   {:.3} s", ping_pong_time);
+    println!("incremental: {:.3} s", incremental_time);
+    println!("bitboard:    {:.3} s", bitboard_time);
+}
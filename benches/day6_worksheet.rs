@@ -0,0 +1,31 @@
+//! Times `day6::solve` on a large generated worksheet and checks it against the generator's
+//! answers.
+//! Run with `cargo bench --bench day6_worksheet`.
+
+use advent_of_code_2025::day6::{self, generator::WorksheetGenerator};
+use std::time::Instant;
+
+const PROBLEMS: usize = 200_000;
+
+fn main() {
+    let generated = WorksheetGenerator::new()
+        .with_problems(PROBLEMS)
+        .with_heights(2..=6)
+        .with_digits(1..=4)
+        .generate();
+    let input = generated.text();
+
+    let start = Instant::now();
+    let (part1, part2) = day6::solve_as::<u128, _>(input.as_bytes()).expect("Solver failed");
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(Some(part1), generated.part1(), "Part 1 disagrees");
+    assert_eq!(Some(part2), generated.part2(), "Part 2 disagrees");
+
+    println!(
+        "{} problems, {} lines of {} bytes",
+        PROBLEMS,
+        input.lines().count(),
+        input.lines().next().map_or(0, str::len)
+    );
+    println!("solve: {:.3} s", elapsed);
+}
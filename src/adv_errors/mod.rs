@@ -31,3 +31,18 @@ impl From<std::io::Error> for UpdateError {
         UpdateError::Io(err)
     }
 }
+
+/// Prefixes the message of an `InvalidInput` error with where it happened, e.g. `Line 3`.
+pub(crate) fn with_context(err: UpdateError, context: impl fmt::Display) -> UpdateError {
+    match err {
+        UpdateError::InvalidInput(msg) => {
+            UpdateError::InvalidInput(format!("{}: {}", context, msg))
+        }
+        e => e,
+    }
+}
+
+/// Prefixes the message of an `InvalidInput` error with its line number.
+pub(crate) fn locate(err: UpdateError, line: usize) -> UpdateError {
+    with_context(err, format_args!("Line {}", line))
+}
@@ -2,10 +2,15 @@ use crate::adv_errors::UpdateError;
 use crate::day2::digits::{
     DigitsU64, digit_ranges, divisors_for, is_minimal_block, pow10, pow10_minus1,
 };
+use crate::day5::{Range, find_overlaps, merge_ranges};
 
 pub struct Day2Accumulator {
     sum_part1: u64,
     sum_part2: u64,
+    merge: bool,
+    ranges: Vec<Range<u64>>, // only recorded when merging
+    dedup_sum_part1: u64,
+    dedup_sum_part2: u64,
 }
 
 impl Day2Accumulator {
@@ -13,6 +18,19 @@ impl Day2Accumulator {
         Day2Accumulator {
             sum_part1: 0,
             sum_part2: 0,
+            merge: false,
+            ranges: Vec::new(),
+            dedup_sum_part1: 0,
+            dedup_sum_part2: 0,
+        }
+    }
+
+    /// Creates an accumulator that additionally records every range, so that
+    /// `finalize_input` can merge overlapping ranges and compute deduplicated sums.
+    pub fn with_merge() -> Self {
+        Day2Accumulator {
+            merge: true,
+            ..Self::new()
         }
     }
 
@@ -59,46 +77,37 @@ impl Day2Accumulator {
             )));
         }
 
-        // Get the digit ranges in [num1, num2]
-        let ranges = digit_ranges(num1, num2);
-        for (start, end, digits) in ranges {
-            for &block_digits_u32 in divisors_for(digits) {
-                // Instead of brute forcing, we directly calculate the possible repeated numbers.
-                let block_digits = DigitsU64::new(block_digits_u32).unwrap();
-
-                // rep_factor is the number that when multiplied by the block gives a full repeating number.
-                // Example: if digits = 8 and block_digits = 2, then rep_factor = 1010101
-                // Then if we have the block 12, we get block * rep_factor = 12121212
-                let rep_factor = pow10_minus1(digits) / pow10_minus1(block_digits);
-
-                // Determine the minimal and maximal blocks that, when repeated, lie within [start, end].
-                // The + rep_factor -1 trick rounds up the start to the nearest multiple of rep_factor
-                let block_start =
-                    ((start + rep_factor - 1) / rep_factor).max(pow10(block_digits - 1));
-                let block_end = (end / rep_factor).min(pow10_minus1(block_digits));
-
-                // Skip if there is no valid block in this range
-                if block_end < block_start {
-                    continue;
-                }
-                for block in block_start..=block_end {
-                    // We can easily calculate the solution to part1 at the same time
-                    if digits / block_digits == DigitsU64::from(2) {
-                        self.sum_part1 += block * rep_factor;
-                    }
-                    // Only count numbers whose repeated pattern is minimal
-                    // Example: if we have block_digits 2, then the block 11 is not minimal because '1'
-                    // repeats twice within the block. But the block 12 is minimal because there is no
-                    // repetiton inside the block.
-                    if is_minimal_block(block, block_digits) {
-                        self.sum_part2 += block * rep_factor;
-                    }
-                }
-            }
+        if self.merge {
+            self.ranges.push(Range::new(num1, num2));
         }
+
+        let (part1, part2) = sum_invalid_ids(num1, num2);
+        self.sum_part1 += part1;
+        self.sum_part2 += part2;
         Ok(())
     }
 
+    /// Merges all recorded ranges and computes the sums without counting shared IDs twice.
+    /// Returns every pair of input ranges that overlap, so the caller can warn about them.
+    /// Does nothing unless the accumulator was created with `with_merge`.
+    pub fn finalize_input(&mut self) -> Vec<(Range<u64>, Range<u64>)> {
+        if !self.merge {
+            return Vec::new();
+        }
+
+        let overlaps = find_overlaps(&self.ranges);
+
+        self.dedup_sum_part1 = 0;
+        self.dedup_sum_part2 = 0;
+        for range in merge_ranges(self.ranges.clone()) {
+            let (part1, part2) = sum_invalid_ids(range.lower(), range.upper());
+            self.dedup_sum_part1 += part1;
+            self.dedup_sum_part2 += part2;
+        }
+
+        overlaps
+    }
+
     pub fn get_sum_part1(&self) -> u64 {
         self.sum_part1
     }
@@ -106,6 +115,14 @@ impl Day2Accumulator {
     pub fn get_sum_part2(&self) -> u64 {
         self.sum_part2
     }
+
+    pub fn get_dedup_sum_part1(&self) -> u64 {
+        self.dedup_sum_part1
+    }
+
+    pub fn get_dedup_sum_part2(&self) -> u64 {
+        self.dedup_sum_part2
+    }
 }
 
 impl Default for Day2Accumulator {
@@ -113,3 +130,47 @@ impl Default for Day2Accumulator {
         Self::new()
     }
 }
+
+/// Sums the invalid IDs in [num1, num2] for both parts.
+fn sum_invalid_ids(num1: u64, num2: u64) -> (u64, u64) {
+    let mut sum_part1 = 0;
+    let mut sum_part2 = 0;
+
+    // Get the digit ranges in [num1, num2]
+    let ranges = digit_ranges(num1, num2);
+    for (start, end, digits) in ranges {
+        for &block_digits_u32 in divisors_for(digits) {
+            // Instead of brute forcing, we directly calculate the possible repeated numbers.
+            let block_digits = DigitsU64::new(block_digits_u32).unwrap();
+
+            // rep_factor is the number that when multiplied by the block gives a full repeating number.
+            // Example: if digits = 8 and block_digits = 2, then rep_factor = 1010101
+            // Then if we have the block 12, we get block * rep_factor = 12121212
+            let rep_factor = pow10_minus1(digits) / pow10_minus1(block_digits);
+
+            // Determine the minimal and maximal blocks that, when repeated, lie within [start, end].
+            // Rounding the division up moves the start to the nearest multiple of rep_factor
+            let block_start = start.div_ceil(rep_factor).max(pow10(block_digits - 1));
+            let block_end = (end / rep_factor).min(pow10_minus1(block_digits));
+
+            // Skip if there is no valid block in this range
+            if block_end < block_start {
+                continue;
+            }
+            for block in block_start..=block_end {
+                // We can easily calculate the solution to part1 at the same time
+                if digits / block_digits == DigitsU64::from(2) {
+                    sum_part1 += block * rep_factor;
+                }
+                // Only count numbers whose repeated pattern is minimal
+                // Example: if we have block_digits 2, then the block 11 is not minimal because '1'
+                // repeats twice within the block. But the block 12 is minimal because there is no
+                // repetiton inside the block.
+                if is_minimal_block(block, block_digits) {
+                    sum_part2 += block * rep_factor;
+                }
+            }
+        }
+    }
+    (sum_part1, sum_part2)
+}
@@ -100,7 +100,7 @@ impl Div<DigitsU64> for DigitsU64 {
     type Output = DigitsU64;
 
     fn div(self, rhs: DigitsU64) -> DigitsU64 {
-        DigitsU64((self.0 as u8 / rhs.0 as u8) as u8)
+        DigitsU64(self.0 / rhs.0)
     }
 }
 
@@ -139,14 +139,14 @@ pub fn pow10_minus1(d: DigitsU64) -> u64 {
 
 #[inline(always)]
 pub fn divisors_for(digits: DigitsU64) -> &'static [u32] {
-    &PROPER_DIVISORS[digits.idx()]
+    PROPER_DIVISORS[digits.idx()]
 }
 
 pub fn is_minimal_block(block: u64, block_digits: DigitsU64) -> bool {
     let total_minus1 = pow10_minus1(block_digits);
     for &d in divisors_for(block_digits) {
         let rep = total_minus1 / (pow10_minus1(DigitsU64::new(d).unwrap()));
-        if block % rep == 0 {
+        if block.is_multiple_of(rep) {
             return false;
         }
     }
@@ -1,44 +1,151 @@
-use crate::adv_errors::UpdateError;
+use crate::adv_errors::{UpdateError, locate};
+use crate::day3::report::BankReport;
+use crate::day3::selection::{DigitSelection, largest_k_digits};
 use log::warn;
+use rayon::prelude::*;
+use std::io::BufRead;
 
 pub struct Day3Accumulator {
-    total_joltage_part1: u64,
-    total_joltage_part2: u64,
+    k_part1: usize,
+    k_part2: usize,
+    total_joltage_part1: u128,
+    total_joltage_part2: u128,
+    line: usize,
+    record_reports: bool,
+    reports: Vec<BankReport>, // only filled when recording reports
+}
+
+/// The outcome of a single bank, before it is added to the totals.
+struct BankResult {
+    joltage_part_1: u128,
+    joltage_part_2: u128,
+    report: Option<BankReport>,
 }
 
 impl Day3Accumulator {
     pub fn new() -> Self {
+        Self::with_k(2, 12)
+    }
+
+    /// Creates an accumulator that switches on `k_part1` and `k_part2` batteries per bank.
+    pub fn with_k(k_part1: usize, k_part2: usize) -> Self {
         Day3Accumulator {
+            k_part1,
+            k_part2,
             total_joltage_part1: 0,
             total_joltage_part2: 0,
+            line: 0,
+            record_reports: false,
+            reports: Vec::new(),
         }
     }
 
+    /// Keeps a `BankReport` of the selected batteries for every processed bank.
+    pub fn with_reports(mut self) -> Self {
+        self.record_reports = true;
+        self
+    }
+
     pub fn update(&mut self, input: &str) -> Result<(), UpdateError> {
-        if input.is_empty() {
+        self.line += 1;
+        if input.trim().is_empty() {
             warn!("Input was empty.");
             return Err(UpdateError::EmptyInput);
         }
 
-        let digits_part_1 = largest_k_digits(input, 2);
-        let joltage_part_1 = digits_part_1
-            .iter()
-            .fold(0u64, |acc, &d| acc * 10 + d as u64);
-        self.total_joltage_part1 += joltage_part_1;
-
-        let digits_part_2 = largest_k_digits(input, 12);
-        let joltage_part_2 = digits_part_2
-            .iter()
-            .fold(0u64, |acc, &d| acc * 10 + d as u64);
-        self.total_joltage_part2 += joltage_part_2;
+        let bank = self.evaluate(self.line, input)?;
+        self.accumulate(bank)
+    }
+
+    /// Processes a whole input at once, evaluating the banks in parallel.
+    /// Blank lines are skipped. An invalid bank fails the whole batch and leaves the
+    /// accumulator unchanged.
+    pub fn update_all(&mut self, input: &str) -> Result<(), UpdateError> {
+        let lines: Vec<&str> = input.lines().collect();
+        self.update_batch(&lines)
+    }
+
+    /// Same as `update_all`, but reads the banks from a reader.
+    pub fn update_reader<R: BufRead>(&mut self, reader: R) -> Result<(), UpdateError> {
+        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
+        self.update_batch(&lines)
+    }
+
+    fn update_batch<S: AsRef<str> + Sync>(&mut self, lines: &[S]) -> Result<(), UpdateError> {
+        let first_line = self.line + 1;
+        let this = &*self;
+
+        // Evaluate in parallel, but accumulate in input order so reports stay sorted
+        let results: Vec<Result<Option<BankResult>, UpdateError>> = lines
+            .par_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let line = line.as_ref();
+                if line.trim().is_empty() {
+                    Ok(None)
+                } else {
+                    this.evaluate(first_line + i, line).map(Some)
+                }
+            })
+            .collect();
+        // Rayon may stop at any failing bank, so the first error is picked in input order
+        let banks: Vec<Option<BankResult>> = results.into_iter().collect::<Result<_, _>>()?;
+
+        // Sum into copies first, so that an overflow also leaves the accumulator unchanged
+        let mut total_part1 = self.total_joltage_part1;
+        let mut total_part2 = self.total_joltage_part2;
+        for bank in banks.iter().flatten() {
+            total_part1 = add_joltage(total_part1, bank.joltage_part_1)?;
+            total_part2 = add_joltage(total_part2, bank.joltage_part_2)?;
+        }
+
+        self.line += lines.len();
+        self.total_joltage_part1 = total_part1;
+        self.total_joltage_part2 = total_part2;
+        self.reports
+            .extend(banks.into_iter().flatten().filter_map(|bank| bank.report));
+        Ok(())
+    }
+
+    fn evaluate(&self, line: usize, input: &str) -> Result<BankResult, UpdateError> {
+        let selection_part_1 =
+            largest_k_digits(input, self.k_part1).map_err(|e| locate(e, line))?;
+        let selection_part_2 =
+            largest_k_digits(input, self.k_part2).map_err(|e| locate(e, line))?;
+
+        let joltage_part_1 = joltage(&selection_part_1)?;
+        let joltage_part_2 = joltage(&selection_part_2)?;
+
+        let report = self
+            .record_reports
+            .then(|| BankReport::new(line, input, selection_part_1, selection_part_2));
+
+        Ok(BankResult {
+            joltage_part_1,
+            joltage_part_2,
+            report,
+        })
+    }
+
+    fn accumulate(&mut self, bank: BankResult) -> Result<(), UpdateError> {
+        self.total_joltage_part1 = add_joltage(self.total_joltage_part1, bank.joltage_part_1)?;
+        self.total_joltage_part2 = add_joltage(self.total_joltage_part2, bank.joltage_part_2)?;
+
+        if let Some(report) = bank.report {
+            self.reports.push(report);
+        }
         Ok(())
     }
 
-    pub fn get_total_joltage_part1(&self) -> u64 {
+    pub fn get_reports(&self) -> &[BankReport] {
+        &self.reports
+    }
+
+    pub fn get_total_joltage_part1(&self) -> u128 {
         self.total_joltage_part1
     }
 
-    pub fn get_total_joltage_part2(&self) -> u64 {
+    pub fn get_total_joltage_part2(&self) -> u128 {
         self.total_joltage_part2
     }
 }
@@ -49,27 +156,17 @@ impl Default for Day3Accumulator {
     }
 }
 
-fn largest_k_digits(input: &str, k: usize) -> Vec<u8> {
-    let mut result: Vec<u8> = Vec::new();
-    let mut remaining = input.len();
-
-    for c in input.chars() {
-        let digit = c.to_digit(10).unwrap_or(0) as u8;
-
-        while let Some(&last) = result.last() {
-            if last < digit && result.len() + remaining > k {
-                result.pop();
-            } else {
-                break;
-            }
-        }
-
-        if result.len() < k {
-            result.push(digit);
-        }
-
-        remaining -= 1;
-    }
+fn joltage(selection: &DigitSelection) -> Result<u128, UpdateError> {
+    selection.to_u128().ok_or_else(|| {
+        UpdateError::InvalidInput(format!(
+            "A joltage of {} digits does not fit into u128!",
+            selection.len()
+        ))
+    })
+}
 
-    result
+fn add_joltage(total: u128, joltage: u128) -> Result<u128, UpdateError> {
+    total
+        .checked_add(joltage)
+        .ok_or_else(|| UpdateError::InvalidInput("Total joltage overflowed u128!".into()))
 }
@@ -1 +1,3 @@
 pub mod accumulator;
+pub mod report;
+pub mod selection;
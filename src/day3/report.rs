@@ -0,0 +1,96 @@
+use crate::adv_errors::UpdateError;
+use crate::day3::selection::DigitSelection;
+use std::io::Write;
+
+const HIGHLIGHT_START: &str = "\x1b[1;32m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/// The batteries switched on in a single bank for both parts.
+#[derive(Clone, Debug)]
+pub struct BankReport {
+    line: usize,
+    bank: String,
+    part1: DigitSelection,
+    part2: DigitSelection,
+}
+
+impl BankReport {
+    pub fn new(line: usize, bank: &str, part1: DigitSelection, part2: DigitSelection) -> Self {
+        BankReport {
+            line,
+            bank: bank.to_string(),
+            part1,
+            part2,
+        }
+    }
+
+    /// 1-based line number of the bank in the input.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn bank(&self) -> &str {
+        &self.bank
+    }
+
+    pub fn part1(&self) -> &DigitSelection {
+        &self.part1
+    }
+
+    pub fn part2(&self) -> &DigitSelection {
+        &self.part2
+    }
+}
+
+/// Renders the bank with the selected batteries highlighted using ANSI colours.
+pub fn highlight(bank: &str, selection: &DigitSelection) -> String {
+    let mut chosen = selection.positions().iter().peekable();
+    let mut out = String::with_capacity(bank.len());
+
+    for (pos, c) in bank.chars().enumerate() {
+        if c == '\r' {
+            continue;
+        }
+        if chosen.next_if_eq(&&pos).is_some() {
+            out.push_str(HIGHLIGHT_START);
+            out.push(c);
+            out.push_str(HIGHLIGHT_END);
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Writes one CSV row per bank. Selected positions are separated by spaces within their column.
+pub fn write_csv<W: Write>(reports: &[BankReport], mut writer: W) -> Result<(), UpdateError> {
+    writeln!(
+        writer,
+        "line,part1_positions,part1_joltage,part2_positions,part2_joltage"
+    )?;
+
+    for report in reports {
+        writeln!(
+            writer,
+            "{},{},{},{},{}",
+            report.line,
+            join_positions(&report.part1),
+            report.part1.to_decimal_string(),
+            join_positions(&report.part2),
+            report.part2.to_decimal_string()
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn join_positions(selection: &DigitSelection) -> String {
+    selection
+        .positions()
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
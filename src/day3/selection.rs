@@ -0,0 +1,108 @@
+use crate::adv_errors::UpdateError;
+
+/// The digits picked out of a bank, in the order they appear in the line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DigitSelection {
+    digits: Vec<u8>,
+    positions: Vec<usize>,
+}
+
+impl DigitSelection {
+    pub fn digits(&self) -> &[u8] {
+        &self.digits
+    }
+
+    /// Character positions of the chosen digits within the input line.
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    pub fn len(&self) -> usize {
+        self.digits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Returns the selected digits as a number, or `None` if it does not fit into a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.digits
+            .iter()
+            .try_fold(0u128, |acc, &d| acc.checked_mul(10)?.checked_add(d as u128))
+    }
+
+    /// Returns the selected digits as a decimal string, which works for any number of digits.
+    pub fn to_decimal_string(&self) -> String {
+        self.digits.iter().map(|&d| char::from(b'0' + d)).collect()
+    }
+}
+
+/// Picks the k digits from the input that form the largest possible number while keeping their order.
+pub fn largest_k_digits(input: &str, k: usize) -> Result<DigitSelection, UpdateError> {
+    select_k_digits(input, k, |last, digit| last < digit)
+}
+
+/// Picks the k digits from the input that form the smallest possible number while keeping their order.
+pub fn smallest_k_digits(input: &str, k: usize) -> Result<DigitSelection, UpdateError> {
+    select_k_digits(input, k, |last, digit| last > digit)
+}
+
+/// Parses a bank into its digits and their character positions.
+/// Whitespace (including the `\r` of CRLF line endings) is skipped, anything else is rejected.
+fn parse_bank(input: &str) -> Result<Vec<(usize, u8)>, UpdateError> {
+    input
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| !c.is_whitespace())
+        .map(|(pos, c)| match c.to_digit(10) {
+            Some(d) => Ok((pos, d as u8)),
+            None => Err(UpdateError::InvalidInput(format!(
+                "Invalid battery '{}' at position {}!",
+                c.escape_debug(),
+                pos
+            ))),
+        })
+        .collect()
+}
+
+/// Greedy monotonic stack: a previously chosen digit is replaced whenever `replace(last, digit)`
+/// holds and there are still enough digits left to fill up all k slots.
+fn select_k_digits(
+    input: &str,
+    k: usize,
+    replace: fn(u8, u8) -> bool,
+) -> Result<DigitSelection, UpdateError> {
+    let bank = parse_bank(input)?;
+    if bank.len() < k {
+        return Err(UpdateError::InvalidInput(format!(
+            "Bank has only {} batteries, but {} are required!",
+            bank.len(),
+            k
+        )));
+    }
+
+    let mut digits: Vec<u8> = Vec::with_capacity(k);
+    let mut positions: Vec<usize> = Vec::with_capacity(k);
+    let mut remaining = bank.len();
+
+    for (pos, digit) in bank {
+        while let Some(&last) = digits.last() {
+            if replace(last, digit) && digits.len() + remaining > k {
+                digits.pop();
+                positions.pop();
+            } else {
+                break;
+            }
+        }
+
+        if digits.len() < k {
+            digits.push(digit);
+            positions.push(pos);
+        }
+
+        remaining -= 1;
+    }
+
+    Ok(DigitSelection { digits, positions })
+}
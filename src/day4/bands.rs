@@ -0,0 +1,292 @@
+use crate::adv_errors::UpdateError;
+use crate::day4::kernel::{Boundary, Kernel};
+use crate::day4::render::cell_char;
+use crate::day4::rules::{Cell, Rule};
+use crate::day4::solver::{Rows, parse_row, step_rows};
+use log::warn;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of cells removed and born in one generation of a banded run.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenerationCounts {
+    removed: u64,
+    born: u64,
+}
+
+impl GenerationCounts {
+    pub fn removed(&self) -> u64 {
+        self.removed
+    }
+
+    pub fn born(&self) -> u64 {
+        self.born
+    }
+}
+
+/// Runs the day 4 automaton on grids that do not fit into memory.
+///
+/// Every generation streams the grid through a window of `band_rows` rows plus the kernel
+/// radius above and below it, computes the band and appends it to a temporary file, which
+/// the next generation reads in turn. Only the window and one band are held in memory.
+pub struct BandSolver {
+    kernel: Kernel,
+    rule: Rule,
+    boundary: Boundary,
+    band_rows: usize,
+    temp_dir: PathBuf,
+}
+
+impl BandSolver {
+    pub fn new() -> Self {
+        BandSolver {
+            kernel: Kernel::moore(),
+            rule: Rule::default(),
+            boundary: Boundary::Empty,
+            band_rows: 1024,
+            temp_dir: env::temp_dir(),
+        }
+    }
+
+    pub fn with_kernel(mut self, kernel: Kernel) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Only `Empty` and `Reflect` are supported: a wrapping grid needs its last rows
+    /// before the first band can be computed.
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// The number of rows computed per band, at least one.
+    pub fn with_band_rows(mut self, band_rows: usize) -> Self {
+        self.band_rows = band_rows.max(1);
+        self
+    }
+
+    /// Where the intermediate generations are stored, the system temp directory by default.
+    pub fn with_temp_dir(mut self, temp_dir: PathBuf) -> Self {
+        self.temp_dir = temp_dir;
+        self
+    }
+
+    /// Reads the grid from `reader` and runs it like `Day4Solver::run_until_stable`,
+    /// then writes the final generation to `output` in the text format of `render_text`.
+    /// Returns the counts of every generation that changed the grid.
+    pub fn run<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        output: W,
+        max_generations: Option<usize>,
+    ) -> Result<Vec<GenerationCounts>, UpdateError> {
+        if self.boundary == Boundary::Wrap {
+            return Err(UpdateError::InvalidInput(
+                "Banded processing does not support a wrapping boundary!".into(),
+            ));
+        }
+
+        // Generations alternate between two files, like the ping-pong buffers
+        let files = [TempFile::new(&self.temp_dir), TempFile::new(&self.temp_dir)];
+        let mut source = RowSource::Text {
+            lines: reader.lines(),
+            line: 0,
+            width: None,
+        };
+        let mut history = Vec::new();
+        let mut generation = 0;
+
+        while max_generations.is_none_or(|max| generation < max) {
+            let target = &files[generation % 2].path;
+            let (counts, changed, width) =
+                self.step(&mut source, BufWriter::new(File::create(target)?))?;
+            generation += 1;
+
+            source = RowSource::Raw {
+                reader: BufReader::new(File::open(target)?),
+                width,
+            };
+            if changed == 0 {
+                break;
+            }
+            history.push(counts);
+        }
+
+        source.write_text(output)?;
+        Ok(history)
+    }
+
+    /// Computes one generation band by band from `source` into `sink`.
+    /// Returns the counts, the number of changed cells and the grid width.
+    fn step<R: BufRead, W: Write>(
+        &self,
+        source: &mut RowSource<R>,
+        mut sink: W,
+    ) -> Result<(GenerationCounts, u64, usize), UpdateError> {
+        let radius = self.kernel.size() / 2;
+        let mut window: Vec<Cell> = Vec::new();
+        let mut first = 0; // grid row of the first row in the window
+        let mut rows_read = 0;
+        let mut width = 0;
+        let mut eof = false;
+        let mut next_row = 0; // first row that is not computed yet
+        let mut band = Vec::new();
+        let mut counts = GenerationCounts::default();
+        let mut changed = 0u64;
+
+        loop {
+            // The kernel of the band's last row reads `radius` rows further down
+            while !eof && rows_read < next_row + self.band_rows + radius {
+                match source.next_row()? {
+                    Some(row) => {
+                        width = row.len();
+                        window.extend(row);
+                        rows_read += 1;
+                    }
+                    None => eof = true,
+                }
+            }
+
+            let band_end = if eof {
+                rows_read
+            } else {
+                next_row + self.band_rows
+            };
+            if band_end == next_row {
+                break;
+            }
+
+            // Until the end of the input, the rows read so far stand in for the height.
+            // Every row the band reads lies within them, so both boundaries resolve the same.
+            let input = Rows {
+                cells: &window,
+                first,
+                width,
+                height: rows_read,
+            };
+            band.resize((band_end - next_row) * width, 0);
+            let stats = step_rows(
+                &input,
+                &mut band,
+                next_row,
+                &self.kernel,
+                self.boundary,
+                &self.rule,
+            );
+            let bytes: Vec<u8> = band.iter().map(|&v| v as u8).collect();
+            sink.write_all(&bytes)?;
+
+            counts.removed += stats.removed;
+            counts.born += stats.born;
+            changed += stats.changed;
+            next_row = band_end;
+
+            // Drop the rows that no later band reads
+            let keep_from = next_row.saturating_sub(radius).max(first);
+            window.drain(..(keep_from - first) * width);
+            first = keep_from;
+        }
+
+        if rows_read == 0 {
+            return Err(UpdateError::EmptyInput);
+        }
+        sink.flush()?;
+        Ok((counts, changed, width))
+    }
+}
+
+impl Default for BandSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a generation reads its rows from: the puzzle input, or the raw cells
+/// of the previous generation with one byte per cell.
+enum RowSource<R> {
+    Text {
+        lines: Lines<R>,
+        line: usize,
+        width: Option<usize>,
+    },
+    Raw {
+        reader: BufReader<File>,
+        width: usize,
+    },
+}
+
+impl<R: BufRead> RowSource<R> {
+    fn next_row(&mut self) -> Result<Option<Vec<Cell>>, UpdateError> {
+        match self {
+            RowSource::Text { lines, line, width } => {
+                for text in lines.by_ref() {
+                    let text = text?;
+                    *line += 1;
+                    if text.is_empty() {
+                        warn!("Ignored empty line.");
+                        continue;
+                    }
+                    let row = parse_row(&text, *line, *width)?;
+                    *width = Some(row.len());
+                    return Ok(Some(row));
+                }
+                Ok(None)
+            }
+            RowSource::Raw { reader, width } => {
+                let mut bytes = vec![0u8; *width];
+                match reader.read_exact(&mut bytes) {
+                    Ok(()) => Ok(Some(bytes.into_iter().map(|b| b as Cell).collect())),
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+
+    fn write_text<W: Write>(mut self, mut output: W) -> Result<(), UpdateError> {
+        let mut rows = 0;
+        while let Some(row) = self.next_row()? {
+            let mut text: String = row.iter().map(|&v| cell_char(v)).collect();
+            text.push('\n');
+            output.write_all(text.as_bytes())?;
+            rows += 1;
+        }
+        if rows == 0 {
+            return Err(UpdateError::EmptyInput);
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// A uniquely named file in `dir` that is deleted when dropped.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(dir: &Path) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        TempFile {
+            path: dir.join(format!("day4_band_{}_{}.bin", process::id(), n)),
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // The file does not exist if its generation was never computed
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::day4::rules::Cell;
+use crate::day4::solver::StepStats;
+use rayon::prelude::*;
+
+/// A grid packed into one bit per cell, with every row padded to whole `u64` words.
+/// Bit `x % 64` of word `x / 64` holds column `x`; padding bits are always zero.
+pub(crate) struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    current: Vec<u64>,
+    previous: Vec<u64>, // the generation before `current`, to find removed and born cells
+}
+
+impl BitGrid {
+    pub(crate) fn new(width: usize) -> Self {
+        BitGrid {
+            width,
+            height: 0,
+            words_per_row: width.div_ceil(64),
+            current: Vec::new(),
+            previous: Vec::new(),
+        }
+    }
+
+    /// Appends a row during input parsing. Live cells are the positive ones.
+    pub(crate) fn push_row(&mut self, row: &[Cell]) {
+        let start = self.current.len();
+        self.current.resize(start + self.words_per_row, 0);
+        for (x, _) in row.iter().enumerate().filter(|&(_, &v)| v > 0) {
+            self.current[start + x / 64] |= 1 << (x % 64);
+        }
+        self.height += 1;
+    }
+
+    /// Call once all rows are pushed.
+    pub(crate) fn finalize(&mut self) {
+        self.previous = self.current.clone();
+    }
+
+    /// Advances one generation of a two-state Life-like rule on the Moore neighbourhood
+    /// with an empty boundary. Bit n of `birth` / `survival` is set if n neighbours cause
+    /// a birth / survival.
+    pub(crate) fn step(&mut self, birth: u64, survival: u64) -> StepStats {
+        std::mem::swap(&mut self.current, &mut self.previous);
+
+        let wpr = self.words_per_row;
+        let height = self.height;
+        let input = &self.previous;
+        // Padding bits in the last word of every row must stay zero
+        let last_mask = match self.width % 64 {
+            0 => u64::MAX,
+            r => (1u64 << r) - 1,
+        };
+        let empty = vec![0u64; wpr];
+
+        self.current
+            .par_chunks_mut(wpr.max(1))
+            .enumerate()
+            .map(|(y, out)| {
+                let row = |r: isize| -> &[u64] {
+                    if r < 0 || r >= height as isize {
+                        &empty
+                    } else {
+                        &input[r as usize * wpr..(r as usize + 1) * wpr]
+                    }
+                };
+                let (above, here, below) =
+                    (row(y as isize - 1), row(y as isize), row(y as isize + 1));
+                let mut stats = StepStats::default();
+
+                for i in 0..wpr {
+                    let mut counter = Counter::default();
+                    for (r, include_centre) in [(above, true), (here, false), (below, true)] {
+                        let prev = if i > 0 { r[i - 1] } else { 0 };
+                        let next = if i + 1 < wpr { r[i + 1] } else { 0 };
+                        // Neighbour to the west is at x - 1, so shift towards higher columns
+                        counter.add(r[i] << 1 | prev >> 63);
+                        counter.add(r[i] >> 1 | next << 63);
+                        if include_centre {
+                            counter.add(r[i]);
+                        }
+                    }
+
+                    let alive = here[i];
+                    let mut next = 0u64;
+                    for n in 0..=8 {
+                        let with_n = counter.equals(n);
+                        if survival >> n & 1 == 1 {
+                            next |= with_n & alive;
+                        }
+                        if birth >> n & 1 == 1 {
+                            next |= with_n & !alive;
+                        }
+                    }
+                    if i + 1 == wpr {
+                        next &= last_mask;
+                    }
+                    out[i] = next;
+
+                    stats.removed += (alive & !next).count_ones() as u64;
+                    stats.born += (!alive & next).count_ones() as u64;
+                }
+
+                stats.changed = stats.removed + stats.born;
+                stats
+            })
+            .reduce(StepStats::default, StepStats::merge)
+    }
+
+    /// Unpacks the latest generation, marking cells removed by it with -1.
+    pub(crate) fn cells(&self) -> Vec<Cell> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let idx = y * self.words_per_row + x / 64;
+                let bit = 1u64 << (x % 64);
+                match (self.current[idx] & bit != 0, self.previous[idx] & bit != 0) {
+                    (true, _) => 1,
+                    (false, true) => -1,
+                    (false, false) => 0,
+                }
+            })
+            .collect()
+    }
+
+    /// Unpacks the latest generation with only its live cells set.
+    pub(crate) fn live_cells(&self) -> Vec<Cell> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let idx = y * self.words_per_row + x / 64;
+                Cell::from(self.current[idx] & (1 << (x % 64)) != 0)
+            })
+            .collect()
+    }
+
+    /// Row-major indices of the cells that died in the latest generation.
+    pub(crate) fn removed(&self) -> Vec<usize> {
+        self.indices(|current, previous| previous & !current)
+    }
+
+    /// Row-major indices of the cells born in the latest generation.
+    pub(crate) fn born(&self) -> Vec<usize> {
+        self.indices(|current, previous| current & !previous)
+    }
+
+    fn indices<F: Fn(u64, u64) -> u64>(&self, select: F) -> Vec<usize> {
+        let mut out = Vec::new();
+        for (w, (&current, &previous)) in self.current.iter().zip(&self.previous).enumerate() {
+            let mut bits = select(current, previous);
+            let y = w / self.words_per_row;
+            let x0 = (w % self.words_per_row) * 64;
+            while bits != 0 {
+                let x = x0 + bits.trailing_zeros() as usize;
+                out.push(y * self.width + x);
+                bits &= bits - 1;
+            }
+        }
+        out
+    }
+}
+
+/// Counts up to 15 one-bit inputs in 64 lanes at once, as four bit planes.
+#[derive(Default)]
+struct Counter {
+    planes: [u64; 4],
+}
+
+impl Counter {
+    /// Ripple-carry adds one input bit per lane.
+    #[inline]
+    fn add(&mut self, mut carry: u64) {
+        for plane in &mut self.planes {
+            let sum = *plane ^ carry;
+            carry &= *plane;
+            *plane = sum;
+        }
+    }
+
+    /// Lanes whose count equals `n`.
+    #[inline]
+    fn equals(&self, n: u32) -> u64 {
+        self.planes
+            .iter()
+            .enumerate()
+            .fold(u64::MAX, |acc, (bit, &plane)| {
+                acc & if n >> bit & 1 == 1 { plane } else { !plane }
+            })
+    }
+}
@@ -0,0 +1,147 @@
+use crate::day4::kernel::{Boundary, Kernel};
+use crate::day4::rules::{Cell, Rule};
+use crate::day4::solver::StepStats;
+
+/// State for the incremental backend: the weighted count of live neighbours of every cell
+/// and the cells that may change in the next generation.
+///
+/// The next state of a cell only depends on its own state and its neighbour count, so a cell
+/// that did not change and whose neighbours did not change stays as it is. Each generation
+/// therefore only visits the worklist instead of the whole grid.
+pub(crate) struct IncrementalState {
+    counts: Vec<u32>,
+    worklist: Vec<usize>,
+    queued: Vec<bool>,
+    last_removed: Vec<usize>,
+    last_born: Vec<usize>,
+}
+
+impl IncrementalState {
+    /// Counts the neighbours of every cell once and queues every cell that could change.
+    pub(crate) fn new(
+        cells: &[Cell],
+        width: usize,
+        height: usize,
+        kernel: &Kernel,
+        boundary: Boundary,
+        rule: &Rule,
+    ) -> Self {
+        let mut counts = vec![0u32; cells.len()];
+
+        for (idx, &v) in cells.iter().enumerate() {
+            if v > 0 {
+                // Every cell that has this one in its kernel counts it
+                for_each_dependent(idx, width, height, kernel, boundary, |n, w| counts[n] += w);
+            }
+        }
+
+        // Without births, empty cells stay empty
+        let queued: Vec<bool> = cells.iter().map(|&v| v != 0 || rule.has_births()).collect();
+        let worklist = (0..cells.len()).filter(|&idx| queued[idx]).collect();
+
+        IncrementalState {
+            counts,
+            worklist,
+            queued,
+            last_removed: Vec::new(),
+            last_born: Vec::new(),
+        }
+    }
+
+    /// Advances `cells` by one generation in place.
+    pub(crate) fn step(
+        &mut self,
+        cells: &mut [Cell],
+        width: usize,
+        height: usize,
+        kernel: &Kernel,
+        boundary: Boundary,
+        rule: &Rule,
+    ) -> StepStats {
+        // Decide on all updates before applying any, so the generation stays synchronous
+        let worklist = std::mem::take(&mut self.worklist);
+        let mut updates: Vec<(usize, Cell)> = Vec::new();
+        for idx in worklist {
+            self.queued[idx] = false;
+            let old = cells[idx];
+            let new = rule.next(old, || self.counts[idx]);
+            if new != old {
+                updates.push((idx, new));
+            }
+        }
+        updates.sort_unstable();
+
+        let mut stats = StepStats::default();
+        let mut removed = Vec::new();
+        let mut born = Vec::new();
+        for &(idx, new) in &updates {
+            let old = cells[idx];
+            cells[idx] = new;
+            stats.record(rule, old, new);
+
+            if old > 0 && new <= 0 {
+                removed.push(idx);
+            } else if old <= 0 && new > 0 {
+                born.push(idx);
+            }
+            // A changed cell may change again, e.g. a removal marker that turns empty
+            if !self.queued[idx] {
+                self.queued[idx] = true;
+                self.worklist.push(idx);
+            }
+        }
+
+        let (counts, queued, worklist) = (&mut self.counts, &mut self.queued, &mut self.worklist);
+        let changes = removed.iter().map(|&idx| (idx, false));
+        let changes = changes.chain(born.iter().map(|&idx| (idx, true)));
+        for (idx, alive) in changes {
+            for_each_dependent(idx, width, height, kernel, boundary, |n, w| {
+                if alive {
+                    counts[n] += w;
+                } else {
+                    counts[n] -= w;
+                }
+                if rule.needs_count(cells[n]) && !queued[n] {
+                    queued[n] = true;
+                    worklist.push(n);
+                }
+            });
+        }
+
+        self.last_removed = removed;
+        self.last_born = born;
+        stats
+    }
+
+    /// Row-major indices of the cells removed by the latest generation.
+    pub(crate) fn last_removed(&self) -> &[usize] {
+        &self.last_removed
+    }
+
+    /// Row-major indices of the cells born in the latest generation.
+    pub(crate) fn last_born(&self) -> &[usize] {
+        &self.last_born
+    }
+}
+
+/// Calls `f(n, weight)` for every cell `n` whose kernel reads `idx` with a non-zero weight.
+/// A cell may be reported more than once if the boundary maps several offsets onto `idx`.
+fn for_each_dependent<F: FnMut(usize, u32)>(
+    idx: usize,
+    width: usize,
+    height: usize,
+    kernel: &Kernel,
+    boundary: Boundary,
+    mut f: F,
+) {
+    let x = idx % width;
+    let y = idx / width;
+
+    for &(dx, dy, weight) in kernel.offsets() {
+        for ny in boundary.sources(y, dy, height) {
+            for nx in boundary.sources(x, dx, width) {
+                f(ny * width + nx, weight);
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::adv_errors::UpdateError;
+
+/// A square N x N weight kernel with odd N, centred on the cell being updated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Kernel {
+    size: usize,
+    weights: Vec<u32>,
+    offsets: Vec<(isize, isize, u32)>, // (dx, dy, weight) of the non-zero entries
+}
+
+impl Kernel {
+    /// Builds a kernel from row-major weights. The length must be the square of an odd number
+    /// and the weights must sum to at most `u32::MAX`.
+    pub fn new(weights: Vec<u32>) -> Result<Self, UpdateError> {
+        let size = weights.len().isqrt();
+        if size * size != weights.len() || size.is_multiple_of(2) {
+            return Err(UpdateError::InvalidInput(format!(
+                "A kernel with {} weights is not an odd N x N square!",
+                weights.len()
+            )));
+        }
+        weights
+            .iter()
+            .try_fold(0u32, |acc, &w| acc.checked_add(w))
+            .ok_or_else(|| UpdateError::InvalidInput("Kernel weights overflow u32!".into()))?;
+
+        let radius = (size / 2) as isize;
+        let offsets = weights
+            .iter()
+            .enumerate()
+            .filter(|&(_, &w)| w > 0)
+            .map(|(i, &w)| {
+                let dx = (i % size) as isize - radius;
+                let dy = (i / size) as isize - radius;
+                (dx, dy, w)
+            })
+            .collect();
+
+        Ok(Kernel {
+            size,
+            weights,
+            offsets,
+        })
+    }
+
+    /// The 3x3 Moore neighbourhood without the centre cell.
+    pub fn moore() -> Self {
+        Self::new(vec![1, 1, 1, 1, 0, 1, 1, 1, 1]).expect("Moore kernel is valid")
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn weights(&self) -> &[u32] {
+        &self.weights
+    }
+
+    /// (dx, dy, weight) for every non-zero weight, relative to the centre.
+    pub fn offsets(&self) -> &[(isize, isize, u32)] {
+        &self.offsets
+    }
+}
+
+/// What the kernel sees outside the grid.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Everything outside the grid is empty
+    #[default]
+    Empty,
+    /// Opposite edges are connected, making the grid a torus
+    Wrap,
+    /// The grid is mirrored at its edges, so the cell just outside an edge reads the edge cell
+    Reflect,
+}
+
+impl Boundary {
+    /// Maps a possibly out-of-range coordinate on an axis of length `len` into the grid,
+    /// or `None` if it reads as empty.
+    #[inline]
+    pub fn resolve(&self, i: isize, len: usize) -> Option<usize> {
+        let n = len as isize;
+        if (0..n).contains(&i) {
+            return Some(i as usize);
+        }
+        match self {
+            Boundary::Empty => None,
+            Boundary::Wrap => Some(i.rem_euclid(n) as usize),
+            Boundary::Reflect => {
+                let m = i.rem_euclid(2 * n);
+                Some(if m < n { m } else { 2 * n - 1 - m } as usize)
+            }
+        }
+    }
+
+    /// All coordinates `t` in `0..len` for which `resolve(t + d, len) == Some(i)`,
+    /// i.e. the cells whose kernel reads `i` at offset `d`.
+    pub fn sources(&self, i: usize, d: isize, len: usize) -> impl Iterator<Item = usize> {
+        let n = len as isize;
+        let i = i as isize;
+        // At most two residues can resolve to i: itself and its mirror image
+        let (period, residues) = match self {
+            Boundary::Empty => (0, [Some(i), None]),
+            Boundary::Wrap => (n, [Some(i), None]),
+            Boundary::Reflect => (2 * n, [Some(i), Some(2 * n - 1 - i)]),
+        };
+
+        residues.into_iter().flatten().filter_map(move |r| {
+            // Smallest u >= d that is congruent to r, so t = u - d is the first candidate
+            let u = if period == 0 {
+                r
+            } else {
+                d + (r - d).rem_euclid(period)
+            };
+            let t = u - d;
+            (0..n).contains(&t).then_some(t as usize)
+        })
+    }
+}
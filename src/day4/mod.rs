@@ -1 +1,7 @@
+pub mod bands;
+mod bitboard;
+mod incremental;
+pub mod kernel;
+pub mod render;
+pub mod rules;
 pub mod solver;
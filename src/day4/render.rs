@@ -0,0 +1,164 @@
+use crate::adv_errors::UpdateError;
+use crate::day4::rules::Cell;
+use crate::day4::solver::{Day4Solver, Generation};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// How a single frame of the grid is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameFormat {
+    /// The puzzle's `@`/`.` format, with `x` marking removed crates
+    Text,
+    /// Binary greyscale image (P5)
+    Pgm,
+    /// Binary colour image (P6)
+    Ppm,
+}
+
+impl FrameFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            FrameFormat::Text => "txt",
+            FrameFormat::Pgm => "pgm",
+            FrameFormat::Ppm => "ppm",
+        }
+    }
+}
+
+/// Renders the latest generation in the `@`/`.` input format, with `x` for removed crates.
+pub fn render_text(solver: &Day4Solver) -> String {
+    let mut out = String::with_capacity((solver.width() + 1) * solver.height());
+    for row in solver.cells().chunks(solver.width().max(1)) {
+        out.extend(row.iter().map(|&v| cell_char(v)));
+        out.push('\n');
+    }
+    out
+}
+
+/// The text character for a cell: `@` for a crate, `.` for empty and `x` for removed.
+pub(crate) fn cell_char(v: Cell) -> char {
+    match v {
+        v if v > 0 => '@',
+        0 => '.',
+        _ => 'x',
+    }
+}
+
+/// Writes the latest generation as a binary PGM image: crates are black,
+/// removed crates grey and empty cells white.
+pub fn write_pgm<W: Write>(solver: &Day4Solver, mut writer: W) -> Result<(), UpdateError> {
+    write!(writer, "P5\n{} {}\n255\n", solver.width(), solver.height())?;
+    let pixels: Vec<u8> = solver
+        .cells()
+        .iter()
+        .map(|&v| match v {
+            v if v > 0 => 0,
+            0 => 255,
+            _ => 128,
+        })
+        .collect();
+    writer.write_all(&pixels)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the latest generation as a binary PPM image: crates are brown,
+/// removed crates red and empty cells white.
+pub fn write_ppm<W: Write>(solver: &Day4Solver, mut writer: W) -> Result<(), UpdateError> {
+    write!(writer, "P6\n{} {}\n255\n", solver.width(), solver.height())?;
+    let pixels: Vec<u8> = solver
+        .cells()
+        .iter()
+        .flat_map(|&v| match v {
+            v if v > 0 => [110, 70, 30],
+            0 => [255, 255, 255],
+            _ => [220, 30, 30],
+        })
+        .collect();
+    writer.write_all(&pixels)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the latest generation in the given format.
+pub fn write_frame<W: Write>(
+    solver: &Day4Solver,
+    format: FrameFormat,
+    mut writer: W,
+) -> Result<(), UpdateError> {
+    match format {
+        FrameFormat::Text => {
+            writer.write_all(render_text(solver).as_bytes())?;
+            writer.flush()?;
+            Ok(())
+        }
+        FrameFormat::Pgm => write_pgm(solver, writer),
+        FrameFormat::Ppm => write_ppm(solver, writer),
+    }
+}
+
+/// Runs the solver to a fixpoint like `run_until_stable`, writing one numbered frame per
+/// generation into `dir` (`generation_0000` is the input grid).
+pub fn export_generations(
+    solver: &mut Day4Solver,
+    format: FrameFormat,
+    dir: &Path,
+    max_generations: Option<usize>,
+) -> Result<Vec<Generation>, UpdateError> {
+    fs::create_dir_all(dir)?;
+
+    let write = |solver: &Day4Solver, n: usize| -> Result<(), UpdateError> {
+        let path = dir.join(format!("generation_{:04}.{}", n, format.extension()));
+        write_frame(solver, format, BufWriter::new(File::create(path)?))
+    };
+
+    write(solver, 0)?;
+    let mut history = Vec::new();
+    while max_generations.is_none_or(|max| history.len() < max) {
+        let step = solver.run_until_stable(Some(1))?;
+        if step.is_empty() {
+            break;
+        }
+        history.extend(step);
+        write(solver, history.len())?;
+    }
+
+    Ok(history)
+}
+
+/// Runs the solver to a fixpoint like `run_until_stable`, drawing every generation
+/// as text on a terminal with `delay` between frames.
+pub fn animate<W: Write>(
+    solver: &mut Day4Solver,
+    mut writer: W,
+    delay: Duration,
+    max_generations: Option<usize>,
+) -> Result<Vec<Generation>, UpdateError> {
+    let mut history: Vec<Generation> = Vec::new();
+    loop {
+        // Clear the screen and move the cursor to the top left corner
+        write!(
+            writer,
+            "\x1b[2J\x1b[HGeneration {} ({} removed)\n{}",
+            history.len(),
+            history.last().map(|g| g.count()).unwrap_or(0),
+            render_text(solver)
+        )?;
+        writer.flush()?;
+
+        if max_generations.is_some_and(|max| history.len() >= max) {
+            break;
+        }
+        let step = solver.run_until_stable(Some(1))?;
+        if step.is_empty() {
+            break;
+        }
+        history.extend(step);
+        thread::sleep(delay);
+    }
+
+    Ok(history)
+}
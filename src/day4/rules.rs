@@ -0,0 +1,226 @@
+use crate::adv_errors::UpdateError;
+use std::fmt;
+use std::sync::Arc;
+
+/// Cell values in the grid buffers:
+///  * `1` is a live cell (a crate),
+///  * `0` is an empty cell,
+///  * `-1 ..= -(states - 2)` are the dying states of multi-state rules, counted down each generation,
+///  * `-1` with only two states marks a cell that died in the latest generation and is otherwise empty.
+pub type Cell = i8;
+
+/// Decides the next state of a cell from whether it is alive and its weighted neighbour count.
+#[derive(Clone)]
+pub struct Rule {
+    kind: RuleKind,
+    states: u8,
+}
+
+#[derive(Clone)]
+enum RuleKind {
+    /// Live cells survive with at least this many neighbours, nothing is born
+    Threshold(u32),
+    /// Bit n of a mask is set if n neighbours cause a birth / survival
+    LifeLike {
+        birth: u64,
+        survival: u64,
+    },
+    Custom(Arc<dyn Fn(bool, u32) -> bool + Send + Sync>),
+}
+
+impl Rule {
+    /// The day 4 rule: crates with fewer than `threshold` neighbours are removed.
+    pub fn threshold(threshold: u32) -> Self {
+        Rule {
+            kind: RuleKind::Threshold(threshold),
+            states: 2,
+        }
+    }
+
+    /// Conway's Game of Life, `B3/S23`.
+    pub fn conway() -> Self {
+        Self::parse("B3/S23").expect("Conway rule is valid")
+    }
+
+    /// A rule given by a closure `(alive, neighbours) -> alive in the next generation`.
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(bool, u32) -> bool + Send + Sync + 'static,
+    {
+        Rule {
+            kind: RuleKind::Custom(Arc::new(f)),
+            states: 2,
+        }
+    }
+
+    /// Parses a Life-like rule string such as `B3/S23`, optionally followed by a number of
+    /// states for Generations rules, e.g. `B2/S/C3` (Brian's Brain).
+    pub fn parse(rule: &str) -> Result<Self, UpdateError> {
+        let invalid =
+            || UpdateError::InvalidInput(format!("\"{}\" is not a valid B/S rule!", rule));
+
+        let mut parts = rule.trim().split('/');
+        let birth = parts
+            .next()
+            .and_then(|p| p.strip_prefix(['B', 'b']))
+            .ok_or_else(invalid)?;
+        let survival = parts
+            .next()
+            .and_then(|p| p.strip_prefix(['S', 's']))
+            .ok_or_else(invalid)?;
+        let states = match parts.next() {
+            Some(p) => p
+                .strip_prefix(['C', 'c', 'G', 'g'])
+                .unwrap_or(p)
+                .parse::<u8>()
+                .map_err(|_| invalid())?,
+            None => 2,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let mask = |digits: &str| {
+            digits
+                .chars()
+                .try_fold(0u64, |mask, c| match c.to_digit(10) {
+                    Some(d) if d <= 8 => Ok(mask | 1 << d),
+                    _ => Err(invalid()),
+                })
+        };
+
+        Rule {
+            kind: RuleKind::LifeLike {
+                birth: mask(birth)?,
+                survival: mask(survival)?,
+            },
+            states: 2,
+        }
+        .with_states(states)
+    }
+
+    /// Sets the number of cell states. With more than two, dead cells pass through
+    /// `states - 2` dying states before they become empty and can be reborn.
+    pub fn with_states(mut self, states: u8) -> Result<Self, UpdateError> {
+        if !(2..=127).contains(&states) {
+            return Err(UpdateError::InvalidInput(format!(
+                "{} states are not supported, expected 2 to 127!",
+                states
+            )));
+        }
+        self.states = states;
+        Ok(self)
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Whether an empty cell can ever become alive.
+    pub fn has_births(&self) -> bool {
+        match &self.kind {
+            RuleKind::Threshold(_) => false,
+            RuleKind::LifeLike { birth, .. } => *birth != 0,
+            RuleKind::Custom(_) => true,
+        }
+    }
+
+    /// Birth and survival masks (bit n set for n neighbours), if this is a two-state Life-like rule.
+    /// The day 4 rule is reported as one as long as its threshold is at most 9.
+    pub fn as_life_like(&self) -> Option<(u64, u64)> {
+        if self.states != 2 {
+            return None;
+        }
+        match self.kind {
+            RuleKind::LifeLike { birth, survival } => Some((birth, survival)),
+            RuleKind::Threshold(t) if t <= 9 => Some((0, 0x1ff & !((1u64 << t) - 1))),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn next_alive(&self, alive: bool, count: u32) -> bool {
+        match &self.kind {
+            RuleKind::Threshold(t) => alive && count >= *t,
+            RuleKind::LifeLike { birth, survival } => {
+                let mask = if alive { survival } else { birth };
+                count < 64 && mask >> count & 1 == 1
+            }
+            RuleKind::Custom(f) => f(alive, count),
+        }
+    }
+
+    /// Whether a cell value is one of the dying states that can neither count nor be reborn.
+    #[inline]
+    pub fn is_dying(&self, cell: Cell) -> bool {
+        cell < 0 && (-cell) as u8 <= self.states - 2
+    }
+
+    /// Whether the next state of `cell` depends on its neighbour count at all.
+    #[inline]
+    pub fn needs_count(&self, cell: Cell) -> bool {
+        cell > 0 || (self.has_births() && !self.is_dying(cell))
+    }
+
+    /// The next value of a cell. `count` is only called if the neighbours matter.
+    #[inline]
+    pub fn next<F: FnOnce() -> u32>(&self, cell: Cell, count: F) -> Cell {
+        if cell > 0 {
+            if self.next_alive(true, count()) {
+                1
+            } else {
+                -1
+            }
+        } else if self.is_dying(cell) {
+            // Count down through the dying states towards empty
+            if ((-cell) as u8) < self.states - 2 {
+                cell - 1
+            } else {
+                0
+            }
+        } else if self.has_births() && self.next_alive(false, count()) {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Collapses the removal marker into empty, so that two values compare equal
+    /// exactly when the cells behave the same.
+    #[inline]
+    pub fn logical(&self, cell: Cell) -> Cell {
+        if cell > 0 || self.is_dying(cell) {
+            cell
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::threshold(4)
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            RuleKind::Threshold(t) => write!(f, "Threshold({})", t)?,
+            RuleKind::LifeLike { birth, survival } => {
+                let digits = |mask: &u64| -> String {
+                    (0..=8)
+                        .filter(|d| mask >> d & 1 == 1)
+                        .map(|d| char::from(b'0' + d as u8))
+                        .collect()
+                };
+                write!(f, "B{}/S{}", digits(birth), digits(survival))?
+            }
+            RuleKind::Custom(_) => write!(f, "Custom")?,
+        }
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
@@ -1,143 +1,495 @@
 use crate::adv_errors::UpdateError;
+use crate::day4::bitboard::BitGrid;
+use crate::day4::incremental::IncrementalState;
+use crate::day4::kernel::{Boundary, Kernel};
+use crate::day4::rules::{Cell, Rule};
 use log::warn;
 use rayon::prelude::*;
+use std::borrow::Cow;
+
+/// The cells removed and born in a single generation, as (x, y) coordinates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Generation {
+    removed: Vec<(usize, usize)>,
+    born: Vec<(usize, usize)>,
+}
+
+impl Generation {
+    /// The number of removed cells.
+    pub fn count(&self) -> u64 {
+        self.removed.len() as u64
+    }
+
+    pub fn removed(&self) -> &[(usize, usize)] {
+        &self.removed
+    }
+
+    pub fn born(&self) -> &[(usize, usize)] {
+        &self.born
+    }
+}
+
+/// What happened during a single step.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct StepStats {
+    pub(crate) removed: u64,
+    pub(crate) born: u64,
+    /// Cells whose behaviour changed, i.e. ignoring the removal marker turning empty
+    pub(crate) changed: u64,
+}
+
+impl StepStats {
+    #[inline]
+    pub(crate) fn record(&mut self, rule: &Rule, old: Cell, new: Cell) {
+        if old > 0 && new <= 0 {
+            self.removed += 1;
+        } else if old <= 0 && new > 0 {
+            self.born += 1;
+        }
+        if rule.logical(old) != rule.logical(new) {
+            self.changed += 1;
+        }
+    }
+
+    pub(crate) fn merge(self, other: StepStats) -> StepStats {
+        StepStats {
+            removed: self.removed + other.removed,
+            born: self.born + other.born,
+            changed: self.changed + other.changed,
+        }
+    }
+}
+
+/// How `Day4Solver` computes a generation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Sweeps the whole grid every generation, in parallel, using two buffers
+    #[default]
+    PingPong,
+    /// Keeps neighbour counts and only revisits cells next to the latest changes
+    Incremental,
+    /// Packs the grid into one bit per cell and counts neighbours with bitwise adders.
+    /// Supports two-state Life-like rules on the Moore neighbourhood with an empty boundary.
+    Bitboard,
+}
 
 pub struct Day4Solver {
-    matrix: Vec<Vec<i8>>, // only used for input parsing
-    kernel: Vec<u8>,      // 3x3 kernel
-    threshold: u8,
+    kernel: Kernel,
+    rule: Rule,
+    boundary: Boundary,
     width: usize,
     height: usize,
-    buffer_a: Vec<i8>, // ping-pong buffer
-    buffer_b: Vec<i8>,
-    iteration: i8,
+    buffer_a: Vec<Cell>, // ping-pong buffer
+    buffer_b: Vec<Cell>,
+    iteration: usize, // generations since `finalize_input`
+    line: usize,      // input lines seen by `add_row`, for error messages
+    backend: Backend,
+    incremental: Option<IncrementalState>, // built on the first incremental step
+    bits: Option<BitGrid>,                 // replaces both buffers for the bitboard backend
 }
 
 impl Day4Solver {
-    pub fn new(kernel: Vec<u8>, threshold: u8) -> Self {
+    /// Creates a solver that removes crates whose weighted neighbour count is below `threshold`.
+    /// The kernel weights are row-major and must form an odd N x N square.
+    pub fn new(kernel: Vec<u32>, threshold: u32) -> Result<Self, UpdateError> {
+        Ok(Self::with_kernel(Kernel::new(kernel)?, threshold))
+    }
+
+    pub fn with_kernel(kernel: Kernel, threshold: u32) -> Self {
         Day4Solver {
-            matrix: Vec::new(),
             kernel,
-            threshold,
+            rule: Rule::threshold(threshold),
+            boundary: Boundary::Empty,
             width: 0,
             height: 0,
             buffer_a: Vec::new(),
             buffer_b: Vec::new(),
             iteration: 0,
+            line: 0,
+            backend: Backend::PingPong,
+            incremental: None,
+            bits: None,
         }
     }
 
+    /// Selects the backend. Rows added so far are moved into the storage of the new
+    /// backend, keeping only their live cells; call `finalize_input` again afterwards.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        if backend == self.backend || self.height == 0 {
+            self.backend = backend;
+            return self;
+        }
+
+        let live: Vec<Cell> = match &self.bits {
+            Some(bits) => bits.live_cells(),
+            None => self.current().iter().map(|&v| Cell::from(v > 0)).collect(),
+        };
+        self.backend = backend;
+        self.buffer_b = Vec::new();
+        self.incremental = None;
+        if backend == Backend::Bitboard {
+            let mut bits = BitGrid::new(self.width);
+            for row in live.chunks(self.width) {
+                bits.push_row(row);
+            }
+            self.bits = Some(bits);
+            self.buffer_a = Vec::new();
+        } else {
+            self.bits = None;
+            self.buffer_a = live;
+        }
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Replaces the day 4 threshold rule, e.g. with `Rule::conway()`.
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Parses one line of the grid. All rows must have the same width; a rejected row
+    /// leaves the grid unchanged.
     pub fn add_row(&mut self, line: &str) -> Result<(), UpdateError> {
+        self.line += 1;
         if line.is_empty() {
             warn!("Ignored empty line.");
             return Ok(());
         }
 
-        let row: Vec<i8> = line
-            .chars()
-            .map(|c| match c {
-                '@' => Ok(1),
-                '.' => Ok(0),
-                _ => Err(UpdateError::InvalidInput(format!(
-                    "Invalid grid character: {}",
-                    c
-                ))),
-            })
-            .collect::<Result<_, _>>()?;
+        let row = parse_row(line, self.line, (self.height > 0).then_some(self.width))?;
+        if self.height == 0 {
+            self.width = row.len();
+        }
+        self.height += 1;
 
-        self.matrix.push(row);
+        // Rows go straight into the backend's storage, so the input is only held once
+        if self.backend == Backend::Bitboard {
+            self.bits
+                .get_or_insert_with(|| BitGrid::new(row.len()))
+                .push_row(&row);
+        } else {
+            self.buffer_a.extend(row);
+        }
         Ok(())
     }
 
-    pub fn finalize_input(&mut self) {
-        self.height = self.matrix.len();
-        self.width = self.matrix.first().map(|r| r.len()).unwrap_or(0);
+    /// Prepares the backend once all rows are added. Fails if the grid has no rows.
+    pub fn finalize_input(&mut self) -> Result<(), UpdateError> {
+        if self.height == 0 {
+            return Err(UpdateError::EmptyInput);
+        }
+        if self.bits.is_some() != (self.backend == Backend::Bitboard) {
+            return Err(UpdateError::InvalidInput(format!(
+                "The stored rows do not match the {:?} backend!",
+                self.backend
+            )));
+        }
+        if let Some(bits) = &mut self.bits {
+            bits.finalize();
+        } else {
+            // initialize ping-pong buffer_b
+            self.buffer_b = vec![0; self.width * self.height];
+        }
+
+        self.iteration = 0;
+        self.incremental = None;
+        Ok(())
+    }
 
-        let len = self.width * self.height;
+    /// Advances the grid by one generation and returns the number of removed cells.
+    pub fn solve(&mut self) -> Result<u64, UpdateError> {
+        Ok(self.step()?.removed)
+    }
 
-        // flatten input into buffer_a
-        self.buffer_a = self
-            .matrix
-            .iter()
-            .flat_map(|r| r.iter().map(|&v| v as i8))
-            .collect();
+    fn step(&mut self) -> Result<StepStats, UpdateError> {
+        let stats = match self.backend {
+            Backend::PingPong => self.step_ping_pong(),
+            Backend::Incremental => self.step_incremental(),
+            Backend::Bitboard => self.step_bitboard()?,
+        };
+        self.iteration += 1;
+        Ok(stats)
+    }
 
-        // initialize ping-pong buffer_b
-        self.buffer_b = vec![0; len];
+    fn step_bitboard(&mut self) -> Result<StepStats, UpdateError> {
+        let (birth, survival) = match self.rule.as_life_like() {
+            Some(masks) if self.kernel == Kernel::moore() && self.boundary == Boundary::Empty => {
+                masks
+            }
+            _ => {
+                return Err(UpdateError::InvalidInput(format!(
+                    "The bitboard backend does not support rule {:?} with a {}x{} kernel and {:?} boundary!",
+                    self.rule,
+                    self.kernel.size(),
+                    self.kernel.size(),
+                    self.boundary
+                )));
+            }
+        };
 
-        self.iteration = 0;
+        let bits = self.bits.as_mut().ok_or_else(|| {
+            UpdateError::InvalidInput("The bitboard backend holds no rows!".into())
+        })?;
+        Ok(bits.step(birth, survival))
     }
 
-    pub fn solve(&mut self) -> Result<u32, UpdateError> {
-        let (input_buf, output_buf) = if self.iteration % 2 == 0 {
+    fn step_incremental(&mut self) -> StepStats {
+        // The incremental backend updates buffer_a in place
+        let state = self.incremental.get_or_insert_with(|| {
+            IncrementalState::new(
+                &self.buffer_a,
+                self.width,
+                self.height,
+                &self.kernel,
+                self.boundary,
+                &self.rule,
+            )
+        });
+        state.step(
+            &mut self.buffer_a,
+            self.width,
+            self.height,
+            &self.kernel,
+            self.boundary,
+            &self.rule,
+        )
+    }
+
+    fn step_ping_pong(&mut self) -> StepStats {
+        let (input_buf, output_buf) = if self.iteration.is_multiple_of(2) {
             (&self.buffer_a, &mut self.buffer_b)
         } else {
             (&self.buffer_b, &mut self.buffer_a)
         };
 
-        self.iteration += 1;
+        let input = Rows {
+            cells: input_buf,
+            first: 0,
+            width: self.width,
+            height: self.height,
+        };
+        step_rows(
+            &input,
+            output_buf,
+            0,
+            &self.kernel,
+            self.boundary,
+            &self.rule,
+        )
+    }
 
-        let width = self.width;
-        let height = self.height;
-        let k = &self.kernel;
+    /// Runs `solve` until the grid stops changing, or until `max_generations` is reached.
+    /// Returns the removed and born cells of every generation that changed the grid.
+    pub fn run_until_stable(
+        &mut self,
+        max_generations: Option<usize>,
+    ) -> Result<Vec<Generation>, UpdateError> {
+        let mut history = Vec::new();
 
-        // Compute accessible counts per row in parallel
-        let row_accessibles: Vec<u32> = output_buf
-            .par_chunks_mut(width)
-            .enumerate()
-            .map(|(y, row)| {
-                let mut accessible_row: u32 = 0;
+        while max_generations.is_none_or(|max| history.len() < max) {
+            if self.step()?.changed == 0 {
+                break;
+            }
+            history.push(Generation {
+                removed: self.removed_cells(),
+                born: self.born_cells(),
+            });
+        }
 
-                for x in 0..width {
-                    let idx = y * width + x;
+        Ok(history)
+    }
 
-                    if input_buf[idx] <= 0 {
-                        row[x] = 0;
-                        continue;
-                    }
+    /// Returns the (x, y) coordinates of the cells removed by the latest generation.
+    pub fn removed_cells(&self) -> Vec<(usize, usize)> {
+        if let Some(state) = &self.incremental {
+            return self.coordinates(state.last_removed().iter().copied());
+        }
+        if let Some(bits) = &self.bits {
+            return self.coordinates(bits.removed().into_iter());
+        }
 
-                    let mut active_neighbors: u8 = 0;
+        // Only cells that died in the latest generation are at the first dying state
+        self.coordinates(
+            self.current()
+                .iter()
+                .enumerate()
+                .filter(|&(_, &v)| v == -1)
+                .map(|(idx, _)| idx),
+        )
+    }
 
-                    for dy in -1..=1 {
-                        let sy = y as isize + dy;
-                        if sy < 0 || sy >= height as isize {
-                            continue;
-                        }
+    /// Returns the (x, y) coordinates of the cells born in the latest generation.
+    pub fn born_cells(&self) -> Vec<(usize, usize)> {
+        if let Some(state) = &self.incremental {
+            return self.coordinates(state.last_born().iter().copied());
+        }
+        if let Some(bits) = &self.bits {
+            return self.coordinates(bits.born().into_iter());
+        }
+        if self.iteration == 0 {
+            return Vec::new();
+        }
 
-                        for dx in -1..=1 {
-                            let sx = x as isize + dx;
-                            if sx < 0 || sx >= width as isize {
-                                continue;
-                            }
+        self.coordinates(
+            self.current()
+                .iter()
+                .zip(self.previous())
+                .enumerate()
+                .filter(|&(_, (&now, &before))| now > 0 && before <= 0)
+                .map(|(idx, _)| idx),
+        )
+    }
 
-                            if input_buf[sy as usize * width + sx as usize] > 0 {
-                                let ki = (dy + 1) * 3 + (dx + 1);
-                                active_neighbors += k[ki as usize];
-                            }
-                        }
-                    }
+    fn coordinates<I: Iterator<Item = usize>>(&self, indices: I) -> Vec<(usize, usize)> {
+        indices
+            .map(|idx| (idx % self.width, idx / self.width))
+            .collect()
+    }
 
-                    if active_neighbors >= self.threshold {
-                        row[x] = 1;
-                    } else {
-                        row[x] = -1;
-                        accessible_row += 1;
-                    }
-                }
+    /// Number of generations computed since `finalize_input`.
+    pub fn generation(&self) -> usize {
+        self.iteration
+    }
 
-                accessible_row
-            })
-            .collect();
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-        // Sum the accessible counts from all rows
-        let accessible_total: u32 = row_accessibles.iter().sum();
+    pub fn height(&self) -> usize {
+        self.height
+    }
 
-        Ok(accessible_total)
+    /// Row-major cells of the latest generation: 1 is a crate, 0 is empty
+    /// and negative values are removed or dying cells (see `Cell`).
+    /// The bitboard backend unpacks its bits into a new buffer.
+    pub fn cells(&self) -> Cow<'_, [Cell]> {
+        match &self.bits {
+            Some(bits) => Cow::Owned(bits.cells()),
+            None => Cow::Borrowed(self.current()),
+        }
+    }
+
+    /// The buffer holding the latest generation.
+    fn current(&self) -> &[Cell] {
+        if self.backend == Backend::Incremental || self.iteration.is_multiple_of(2) {
+            &self.buffer_a
+        } else {
+            &self.buffer_b
+        }
+    }
+
+    /// The buffer holding the generation before the latest one (ping-pong backend only).
+    fn previous(&self) -> &[Cell] {
+        if self.iteration.is_multiple_of(2) {
+            &self.buffer_b
+        } else {
+            &self.buffer_a
+        }
     }
 }
 
 impl Default for Day4Solver {
     fn default() -> Self {
-        Self::new(vec![1, 1, 1, 1, 0, 1, 1, 1, 1], 4)
+        Self::with_kernel(Kernel::moore(), 4)
     }
 }
+
+/// Parses one line of the puzzle input into cells. `line` is the 1-based input line used
+/// in error messages, `width` the width the row must have, if already known.
+pub(crate) fn parse_row(
+    text: &str,
+    line: usize,
+    width: Option<usize>,
+) -> Result<Vec<Cell>, UpdateError> {
+    let row: Vec<Cell> = text
+        .chars()
+        .enumerate()
+        .map(|(x, c)| match c {
+            '@' => Ok(1),
+            '.' => Ok(0),
+            _ => Err(UpdateError::InvalidInput(format!(
+                "Line {}: Invalid grid character '{}' at column {}!",
+                line,
+                c,
+                x + 1
+            ))),
+        })
+        .collect::<Result<_, _>>()?;
+
+    match width {
+        Some(width) if row.len() != width => Err(UpdateError::InvalidInput(format!(
+            "Line {}: Row has {} cells, but the grid is {} wide!",
+            line,
+            row.len(),
+            width
+        ))),
+        _ => Ok(row),
+    }
+}
+
+/// Consecutive rows of a grid that is `height` rows tall, starting at row `first`.
+pub(crate) struct Rows<'a> {
+    pub(crate) cells: &'a [Cell],
+    pub(crate) first: usize,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+/// Computes the next generation of the rows in `output`, the first of which is grid row
+/// `first_row`, in parallel. `input` must hold every row the kernel reads for them.
+pub(crate) fn step_rows(
+    input: &Rows,
+    output: &mut [Cell],
+    first_row: usize,
+    kernel: &Kernel,
+    boundary: Boundary,
+    rule: &Rule,
+) -> StepStats {
+    let Rows {
+        cells,
+        first,
+        width,
+        height,
+    } = *input;
+    let offsets = kernel.offsets();
+
+    output
+        .par_chunks_mut(width.max(1))
+        .enumerate()
+        .map(|(i, row)| {
+            let y = first_row + i;
+            let mut stats = StepStats::default();
+
+            for (x, cell) in row.iter_mut().enumerate() {
+                let old = cells[(y - first) * width + x];
+
+                let active_neighbors = || {
+                    let mut active: u32 = 0;
+                    for &(dx, dy, weight) in offsets {
+                        let Some(sy) = boundary.resolve(y as isize + dy, height) else {
+                            continue;
+                        };
+                        let Some(sx) = boundary.resolve(x as isize + dx, width) else {
+                            continue;
+                        };
+
+                        if cells[(sy - first) * width + sx] > 0 {
+                            active += weight;
+                        }
+                    }
+                    active
+                };
+
+                *cell = rule.next(old, active_neighbors);
+                stats.record(rule, old, *cell);
+            }
+
+            stats
+        })
+        .reduce(StepStats::default, StepStats::merge)
+}
@@ -0,0 +1,104 @@
+use crate::day5::interval_set::IntervalSet;
+use crate::day5::{Bound, Range};
+use std::fmt;
+
+/// Why an ingredient is fresh or spoiled. Displays as e.g. `5: fresh via 3-5 (#1) in merged
+/// range #1`, numbering ranges from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IngredientAudit<T = i64> {
+    ingredient: T,
+    sources: Vec<(usize, Range<T>)>,
+    merged_index: Option<usize>,
+}
+
+impl<T: Bound> IngredientAudit<T> {
+    pub fn ingredient(&self) -> T {
+        self.ingredient
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        self.merged_index.is_some()
+    }
+
+    /// The input ranges containing the ingredient, with their index in the input, in input order.
+    pub fn sources(&self) -> &[(usize, Range<T>)] {
+        &self.sources
+    }
+
+    /// The index of the merged range containing the ingredient, see `IntervalSet::ranges`.
+    pub fn merged_index(&self) -> Option<usize> {
+        self.merged_index
+    }
+}
+
+impl<T: Bound> fmt::Display for IngredientAudit<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.merged_index {
+            None => write!(f, "{}: spoiled", self.ingredient),
+            Some(merged) => {
+                write!(f, "{}: fresh via", self.ingredient)?;
+                for (i, (idx, range)) in self.sources.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    write!(f, "{} {} (#{})", sep, range, idx + 1)?;
+                }
+                write!(f, " in merged range #{}", merged + 1)
+            }
+        }
+    }
+}
+
+/// Audits ingredients one at a time against a fixed list of ranges.
+pub struct Auditor<T = i64> {
+    merged: IntervalSet<T>,
+    groups: Vec<Vec<(usize, Range<T>)>>, // input ranges by the merged range they lie in
+}
+
+impl<T: Bound> Auditor<T> {
+    pub fn new(ranges: &[Range<T>]) -> Self {
+        let merged = IntervalSet::new(ranges.to_vec());
+
+        // Every input range lies in exactly one merged range, so only that group needs checking
+        let mut groups: Vec<Vec<(usize, Range<T>)>> = vec![Vec::new(); merged.len()];
+        for (idx, &range) in ranges.iter().enumerate() {
+            if let Some(merged_index) = merged.position(range.lower()) {
+                groups[merged_index].push((idx, range));
+            }
+        }
+
+        Auditor { merged, groups }
+    }
+
+    /// The merged ranges that `IngredientAudit::merged_index` refers to.
+    pub fn fresh_ranges(&self) -> &IntervalSet<T> {
+        &self.merged
+    }
+
+    pub fn audit(&self, ingredient: T) -> IngredientAudit<T> {
+        let merged_index = self.merged.position(ingredient);
+        let sources = merged_index
+            .map(|m| {
+                self.groups[m]
+                    .iter()
+                    .filter(|(_, r)| r.contains(ingredient))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        IngredientAudit {
+            ingredient,
+            sources,
+            merged_index,
+        }
+    }
+}
+
+/// Audits every ingredient against the ranges, as returned by `read_ranges_ingredients`.
+/// Records are in ingredient order.
+pub fn audit_ingredients<T: Bound>(
+    ranges: &[Range<T>],
+    ingredients: &[T],
+) -> Vec<IngredientAudit<T>> {
+    let auditor = Auditor::new(ranges);
+    ingredients.iter().map(|&i| auditor.audit(i)).collect()
+}
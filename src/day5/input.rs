@@ -0,0 +1,102 @@
+use crate::adv_errors::{UpdateError, locate};
+use crate::day5::{Bound, Range};
+use std::io::BufRead;
+
+/// Reads day 5 input in two phases: first the fresh ranges, then the ingredients one at a time,
+/// so the ingredients never need to be held in memory.
+///
+/// Bounds and ingredients may be signed (`-5--2`) and surrounded by whitespace, `#` starts a
+/// comment and line endings may be CRLF. The first blank line after the ranges separates them
+/// from the ingredients; without one the whole input is ranges, which is enough for part 2.
+/// Errors name the offending line.
+pub struct InputReader<R> {
+    reader: R,
+    buffer: String,
+    line: usize,
+}
+
+impl<R: BufRead> InputReader<R> {
+    pub fn new(reader: R) -> Self {
+        InputReader {
+            reader,
+            buffer: String::new(),
+            line: 0,
+        }
+    }
+
+    /// Reads the ranges up to the blank separator line or the end of the input.
+    pub fn read_ranges<T: Bound>(&mut self) -> Result<Vec<Range<T>>, UpdateError> {
+        let mut ranges = Vec::new();
+        while let Some((content, commented)) = self.next_line()? {
+            if content.is_empty() {
+                // The first blank line after the ranges ends them, comments are skipped
+                if !commented && !ranges.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            ranges.push(parse_range(content).map_err(|e| locate(e, self.line))?);
+        }
+
+        if ranges.is_empty() {
+            return Err(UpdateError::EmptyInput);
+        }
+        Ok(ranges)
+    }
+
+    /// Reads the next ingredient, skipping blank and comment lines. Call after `read_ranges`.
+    pub fn next_ingredient<T: Bound>(&mut self) -> Result<Option<T>, UpdateError> {
+        while let Some((content, _)) = self.next_line()? {
+            if !content.is_empty() {
+                return parse_value(content)
+                    .map(Some)
+                    .map_err(|e| locate(e, self.line));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The remaining ingredients, see `next_ingredient`.
+    pub fn ingredients<T: Bound>(&mut self) -> impl Iterator<Item = Result<T, UpdateError>> + '_ {
+        std::iter::from_fn(|| self.next_ingredient().transpose())
+    }
+
+    /// The next line without its comment and surrounding whitespace, and whether it had a comment.
+    fn next_line(&mut self) -> Result<Option<(&str, bool)>, UpdateError> {
+        self.buffer.clear();
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+
+        Ok(Some(match self.buffer.split_once('#') {
+            Some((content, _)) => (content.trim(), true),
+            None => (self.buffer.trim(), false),
+        }))
+    }
+}
+
+/// Parses `a-b` where both bounds may carry a sign, e.g. `-5 - -2`.
+fn parse_range<T: Bound>(text: &str) -> Result<Range<T>, UpdateError> {
+    // The separator is the first '-' after the first digit, so a leading sign is skipped
+    let split_idx = text
+        .find(|c: char| c.is_ascii_digit())
+        .and_then(|start| text[start..].find('-').map(|i| start + i))
+        .ok_or_else(|| {
+            UpdateError::InvalidInput(format!("\"{}\" is not a range like \"3-5\"!", text))
+        })?;
+
+    let lower = parse_value(text[..split_idx].trim())?;
+    let upper = parse_value(text[split_idx + 1..].trim())?;
+    Ok(Range::new(lower, upper))
+}
+
+fn parse_value<T: Bound>(text: &str) -> Result<T, UpdateError> {
+    text.parse::<T>().map_err(|_| {
+        UpdateError::InvalidInput(format!(
+            "\"{}\" could not be parsed as {}!",
+            text,
+            std::any::type_name::<T>()
+        ))
+    })
+}
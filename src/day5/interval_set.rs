@@ -0,0 +1,126 @@
+use crate::day5::{Bound, Range, merge_ranges};
+
+/// A set of integers stored as sorted, disjoint ranges, `i64` unless stated otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalSet<T = i64> {
+    ranges: Vec<Range<T>>, // sorted by lower bound, no two share a value
+}
+
+impl<T: Bound> IntervalSet<T> {
+    /// Builds the set covering every value of every range, merging overlaps.
+    pub fn new(ranges: Vec<Range<T>>) -> Self {
+        IntervalSet {
+            ranges: merge_ranges(ranges),
+        }
+    }
+
+    /// The disjoint ranges of the set, sorted by lower bound.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    /// The number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Whether `value` lies in one of the ranges, by binary search.
+    pub fn contains(&self, value: T) -> bool {
+        self.position(value).is_some()
+    }
+
+    /// The index of the range containing `value`, by binary search.
+    pub fn position(&self, value: T) -> Option<usize> {
+        // The only candidate is the last range starting at or before `value`
+        let idx = self.ranges.partition_point(|r| r.lower() <= value);
+        (idx > 0 && self.ranges[idx - 1].contains(value)).then(|| idx - 1)
+    }
+
+    /// The number of values in the set, or `None` if it does not fit into a `u128`.
+    pub fn coverage(&self) -> Option<u128> {
+        self.ranges
+            .iter()
+            .try_fold(0u128, |acc, r| acc.checked_add(r.span()?))
+    }
+
+    /// Values in either set.
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let ranges = self.ranges.iter().chain(&other.ranges).copied().collect();
+        IntervalSet::new(ranges)
+    }
+
+    /// Values in both sets.
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let lower = a.lower().max(b.lower());
+            let upper = a.upper().min(b.upper());
+            if lower <= upper {
+                ranges.push(Range::new(lower, upper));
+            }
+            // The range that ends first cannot overlap anything further on
+            if a.upper() < b.upper() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Values in this set but not in `other`.
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for &range in &self.ranges {
+            // Cut the pieces of `other` out of `range` from left to right
+            let mut rest = Some(range);
+            while let Some(r) = rest {
+                // Skip the ranges of `other` that end before `r` starts
+                while j < other.ranges.len() && other.ranges[j].upper() < r.lower() {
+                    j += 1;
+                }
+                let Some(&cut) = other.ranges.get(j).filter(|c| c.lower() <= r.upper()) else {
+                    ranges.push(r);
+                    break;
+                };
+                if cut.lower() > r.lower() {
+                    let before = cut.lower().predecessor().expect("cut starts after r");
+                    ranges.push(Range::new(r.lower(), before));
+                }
+                rest = cut
+                    .upper()
+                    .successor()
+                    .filter(|&next| next <= r.upper())
+                    .map(|next| Range::new(next, r.upper()));
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Values within `bounds` that are not in the set.
+    pub fn complement(&self, bounds: Range<T>) -> IntervalSet<T> {
+        IntervalSet {
+            ranges: vec![bounds],
+        }
+        .difference(self)
+    }
+}
+
+impl<T: Bound> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+}
+
+impl<T: Bound> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        IntervalSet::new(iter.into_iter().collect())
+    }
+}
@@ -1,87 +1,168 @@
+pub mod audit;
+pub mod input;
+pub mod interval_set;
+
 use crate::adv_errors::UpdateError;
+pub use input::InputReader;
+use interval_set::IntervalSet;
+use rayon::prelude::*;
+use std::fmt;
 use std::io::BufRead;
+use std::str::FromStr;
+
+/// Integer types usable as range bounds.
+pub trait Bound: Copy + Ord + fmt::Debug + fmt::Display + FromStr + Send + Sync + 'static {
+    /// `upper - lower + 1` for `lower <= upper`, or `None` if it does not fit into a `u128`.
+    fn span(lower: Self, upper: Self) -> Option<u128>;
+
+    /// The next larger value, or `None` at the maximum.
+    fn successor(self) -> Option<Self>;
+
+    /// The next smaller value, or `None` at the minimum.
+    fn predecessor(self) -> Option<Self>;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {$(
+        impl Bound for $t {
+            #[inline]
+            fn span(lower: Self, upper: Self) -> Option<u128> {
+                // The difference of two values of any of these types is below 2^128,
+                // so it survives the detour through wrapping i128 arithmetic
+                ((upper as i128).wrapping_sub(lower as i128) as u128).checked_add(1)
+            }
+
+            #[inline]
+            fn successor(self) -> Option<Self> {
+                self.checked_add(1)
+            }
 
+            #[inline]
+            fn predecessor(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+        }
+    )*};
+}
+
+impl_bound!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+/// An inclusive range of integers, `i64` unless stated otherwise.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Range {
-    lower: i64,
-    upper: i64,
+pub struct Range<T = i64> {
+    lower: T,
+    upper: T,
 }
 
-impl Range {
-    fn new(a: i64, b: i64) -> Self {
+impl<T: Bound> Range<T> {
+    pub fn new(a: T, b: T) -> Self {
         let lower = a.min(b);
         let upper = a.max(b);
         Range { lower, upper }
     }
 
-    fn contains(&self, value: i64) -> bool {
+    pub fn lower(&self) -> T {
+        self.lower
+    }
+
+    pub fn upper(&self) -> T {
+        self.upper
+    }
+
+    /// Whether `value` lies within the inclusive bounds.
+    pub fn contains(&self, value: T) -> bool {
         value >= self.lower && value <= self.upper
     }
 
-    fn span(&self) -> i64 {
-        self.upper - self.lower + 1
+    /// The number of values in the range, or `None` if it does not fit into a `u128`,
+    /// which only happens for the full `i128` or `u128` range.
+    pub fn span(&self) -> Option<u128> {
+        T::span(self.lower, self.upper)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Range<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.lower, self.upper)
     }
 }
 
-pub fn read_ranges_ingredients<R: BufRead>(
+/// Reads the fresh ranges, then a blank line and the ingredients, one per line.
+/// See `InputReader` for the accepted format; use it directly to stream the ingredients.
+pub fn read_ranges_ingredients<T: Bound, R: BufRead>(
     reader: R,
-) -> Result<(Vec<Range>, Vec<i64>), UpdateError> {
-    let lines: Vec<String> = reader
-        .lines()
-        .collect::<Result<_, _>>()
-        .map_err(|_| UpdateError::EmptyInput)?;
-
-    let split_idx =
-        lines
-            .iter()
-            .position(|l| l.trim().is_empty())
-            .ok_or(UpdateError::InvalidInput(format!(
-                "Could not find an empty separator line!"
-            )))?;
-
-    let (range_lines, number_lines) = lines.split_at(split_idx);
-
-    let ranges = range_lines
-        .iter()
-        .map(|line| {
-            let (a, b) = line
-                .split_once('-')
-                .ok_or(UpdateError::InvalidInput(format!(
-                    "\"{}\" could not be split on '-'!",
-                    line
-                )))?;
-
-            let lower = a.parse::<i64>().map_err(|_| {
-                UpdateError::InvalidInput(format!("\"{}\" could not be parsed as an integer!", a))
-            })?;
-            let upper = b.parse::<i64>().map_err(|_| {
-                UpdateError::InvalidInput(format!("\"{}\" could not be parsed as an integer!", b))
-            })?;
-
-            Ok(Range::new(lower, upper))
-        })
-        .collect::<Result<Vec<Range>, UpdateError>>()?;
+) -> Result<(Vec<Range<T>>, Vec<T>), UpdateError> {
+    let mut input = InputReader::new(reader);
+    let ranges = input.read_ranges()?;
+    let ingredients = input.ingredients().collect::<Result<_, _>>()?;
+    Ok((ranges, ingredients))
+}
 
-    let numbers = number_lines
-        .iter()
-        .skip(1)
-        .filter(|l| !l.trim().is_empty())
-        .map(|line| {
-            line.parse::<i64>().map_err(|_| {
-                UpdateError::InvalidInput(format!(
-                    "\"{}\" could not be parsed as an integer!",
-                    line
-                ))
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+/// Which ranges `merge_ranges_with` combines.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Only ranges sharing at least one value, so `3-5` and `6-8` stay separate
+    #[default]
+    Overlapping,
+    /// Also ranges that touch, so `3-5` and `6-8` become `3-8`
+    Adjacent,
+}
 
-    Ok((ranges, numbers))
+/// What `merge_ranges_with` did, for data-quality reports.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MergeStats<T = i64> {
+    input_count: usize,
+    merged_count: usize,
+    overlaps: usize,
+    adjacent: usize,
+    largest_gap: Option<Range<T>>,
 }
 
-pub fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
+impl<T: Bound> MergeStats<T> {
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    pub fn merged_count(&self) -> usize {
+        self.merged_count
+    }
+
+    /// Input ranges that shared a value with an earlier one.
+    pub fn overlaps(&self) -> usize {
+        self.overlaps
+    }
+
+    /// Input ranges that were joined only because they touch an earlier one.
+    pub fn adjacent(&self) -> usize {
+        self.adjacent
+    }
+
+    /// The longest run of values between two merged ranges, if there is a gap at all.
+    pub fn largest_gap(&self) -> Option<Range<T>> {
+        self.largest_gap
+    }
+}
+
+pub fn merge_ranges<T: Bound>(ranges: Vec<Range<T>>) -> Vec<Range<T>> {
+    merge_ranges_with(ranges, MergeMode::Overlapping).0
+}
+
+/// Sorts and merges `ranges` according to `mode`, and reports what was merged.
+pub fn merge_ranges_with<T: Bound>(
+    mut ranges: Vec<Range<T>>,
+    mode: MergeMode,
+) -> (Vec<Range<T>>, MergeStats<T>) {
+    let mut stats = MergeStats {
+        input_count: ranges.len(),
+        merged_count: 0,
+        overlaps: 0,
+        adjacent: 0,
+        largest_gap: None,
+    };
     if ranges.is_empty() {
-        return ranges;
+        return (ranges, stats);
     }
 
     ranges.sort_by_key(|r| r.lower);
@@ -91,7 +172,11 @@ pub fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
 
     for range in ranges.into_iter().skip(1) {
         if range.lower <= current.upper {
+            stats.overlaps += 1;
             current.upper = current.upper.max(range.upper);
+        } else if mode == MergeMode::Adjacent && current.upper.successor() == Some(range.lower) {
+            stats.adjacent += 1;
+            current.upper = range.upper;
         } else {
             merged.push(current);
             current = range;
@@ -99,20 +184,102 @@ pub fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
     }
 
     merged.push(current);
-    merged
+
+    stats.merged_count = merged.len();
+    stats.largest_gap = merged
+        .windows(2)
+        .filter_map(|pair| {
+            // Touching ranges leave no gap
+            let first = pair[0].upper.successor()?;
+            let last = pair[1].lower.predecessor()?;
+            (first <= last).then(|| Range::new(first, last))
+        })
+        .max_by_key(|gap| gap.span());
+
+    (merged, stats)
 }
 
-pub fn solve<R: BufRead>(reader: R) -> Result<(i64, i64), UpdateError> {
-    let (ranges, ingredients) = read_ranges_ingredients(reader)?;
+/// Returns every pair of ranges that share at least one value.
+/// Each pair is ordered by lower bound.
+pub fn find_overlaps<T: Bound>(ranges: &[Range<T>]) -> Vec<(Range<T>, Range<T>)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|r| r.lower);
 
-    let merged_ranges = merge_ranges(ranges);
+    let mut overlaps = Vec::new();
+    for (i, a) in sorted.iter().enumerate() {
+        // Sorted by lower bound, so we can stop at the first range that starts after `a` ends
+        for b in sorted[i + 1..].iter().take_while(|b| b.lower <= a.upper) {
+            overlaps.push((*a, *b));
+        }
+    }
+    overlaps
+}
+
+/// Solves both parts with `i128` bounds, wide enough for any signed or unsigned 64-bit IDs.
+pub fn solve<R: BufRead>(reader: R) -> Result<(usize, u128), UpdateError> {
+    let mut input = InputReader::new(reader);
+    let ranges = input.read_ranges::<i128>()?;
+    solve_stream(ranges, &mut input, None)
+}
+
+/// Like `solve`, for ranges and ingredients that are already parsed.
+/// Returns the number of fresh ingredients and the number of values covered by the ranges.
+pub fn solve_ranges<T: Bound>(
+    ranges: Vec<Range<T>>,
+    ingredients: &[T],
+) -> Result<(usize, u128), UpdateError> {
+    let fresh_ranges = IntervalSet::new(ranges);
 
     let fresh = ingredients
         .iter()
-        .filter(|&&value| merged_ranges.iter().any(|r| r.contains(value)))
+        .filter(|&&value| fresh_ranges.contains(value))
         .count();
 
-    let count = merged_ranges.iter().map(|r| r.span()).sum();
+    Ok((fresh, total_coverage(&fresh_ranges)?))
+}
+
+/// Like `solve_ranges`, but streams the ingredients from `input`, so memory only grows with the
+/// number of ranges. With a `batch_size`, that many ingredients are classified in parallel at a time.
+pub fn solve_stream<T: Bound, R: BufRead>(
+    ranges: Vec<Range<T>>,
+    input: &mut InputReader<R>,
+    batch_size: Option<usize>,
+) -> Result<(usize, u128), UpdateError> {
+    let fresh_ranges = IntervalSet::new(ranges);
+    let mut fresh = 0;
+
+    match batch_size {
+        None => {
+            for ingredient in input.ingredients() {
+                if fresh_ranges.contains(ingredient?) {
+                    fresh += 1;
+                }
+            }
+        }
+        Some(batch_size) => {
+            let mut batch = Vec::with_capacity(batch_size.max(1));
+            loop {
+                batch.clear();
+                for ingredient in input.ingredients().take(batch_size.max(1)) {
+                    batch.push(ingredient?);
+                }
+                if batch.is_empty() {
+                    break;
+                }
+                fresh += batch
+                    .par_iter()
+                    .filter(|&&value| fresh_ranges.contains(value))
+                    .count();
+            }
+        }
+    }
+
+    Ok((fresh, total_coverage(&fresh_ranges)?))
+}
 
-    Ok((fresh as i64, count))
+/// The number of values in `set`, or an error if it does not fit into a `u128`.
+pub fn total_coverage<T: Bound>(set: &IntervalSet<T>) -> Result<u128, UpdateError> {
+    set.coverage().ok_or_else(|| {
+        UpdateError::InvalidInput("The ranges cover more than u128::MAX values!".into())
+    })
 }
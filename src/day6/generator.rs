@@ -0,0 +1,192 @@
+use crate::day6::worksheet::Op;
+use crate::rng::XorShift;
+use std::ops::RangeInclusive;
+
+/// The most digits an operand can have in either reading while still fitting into a `u64`.
+pub const MAX_DIGITS: usize = 19;
+
+/// A generated worksheet with its answers, computed without the parser.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedWorksheet {
+    text: String,
+    part1: Option<u128>,
+    part2: Option<u128>,
+}
+
+impl GeneratedWorksheet {
+    /// The worksheet as `day6::solve` reads it.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The sum of the row results, or `None` if a result is not defined or exceeds `u128`.
+    pub fn part1(&self) -> Option<u128> {
+        self.part1
+    }
+
+    /// The sum of the column results, or `None` if a result is not defined or exceeds `u128`.
+    pub fn part2(&self) -> Option<u128> {
+        self.part2
+    }
+}
+
+/// Generates random column-aligned worksheets, reproducible from a seed.
+#[derive(Clone, Debug)]
+pub struct WorksheetGenerator {
+    seed: u64,
+    problems: usize,
+    heights: (usize, usize),
+    digits: (usize, usize),
+    ops: Vec<Op>,
+}
+
+impl WorksheetGenerator {
+    pub fn new() -> Self {
+        WorksheetGenerator {
+            seed: XorShift::DEFAULT_SEED,
+            problems: 1000,
+            heights: (2, 4),
+            digits: (1, 4),
+            ops: vec![Op::Add, Op::Mul],
+        }
+    }
+
+    /// Any seed but zero, which is replaced by the default one.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        if seed != 0 {
+            self.seed = seed;
+        }
+        self
+    }
+
+    /// The number of problems, at least one: an empty worksheet is not valid input.
+    pub fn with_problems(mut self, problems: usize) -> Self {
+        self.problems = problems.max(1);
+        self
+    }
+
+    /// The number of operands per problem, between 1 and `MAX_DIGITS`.
+    pub fn with_heights(mut self, heights: RangeInclusive<usize>) -> Self {
+        self.heights = Self::clamp(heights);
+        self
+    }
+
+    /// The number of columns per problem, between 1 and `MAX_DIGITS`.
+    pub fn with_digits(mut self, digits: RangeInclusive<usize>) -> Self {
+        self.digits = Self::clamp(digits);
+        self
+    }
+
+    /// The operators to choose from, `+` and `*` by default. An empty list keeps the default.
+    pub fn with_ops(mut self, ops: Vec<Op>) -> Self {
+        if !ops.is_empty() {
+            self.ops = ops;
+        }
+        self
+    }
+
+    fn clamp(range: RangeInclusive<usize>) -> (usize, usize) {
+        let lower = (*range.start()).clamp(1, MAX_DIGITS);
+        (lower, (*range.end()).clamp(lower, MAX_DIGITS))
+    }
+
+    pub fn generate(&self) -> GeneratedWorksheet {
+        let mut rng = XorShift::new(self.seed);
+
+        // Every problem as a block of rows of equal width, with its operator
+        let mut blocks = Vec::with_capacity(self.problems);
+        let (mut part1, mut part2) = (Some(0u128), Some(0u128));
+        for _ in 0..self.problems {
+            let height = rng.between(self.heights.0, self.heights.1);
+            let width = rng.between(self.digits.0, self.digits.1);
+            let op = self.ops[rng.below(self.ops.len() as u64) as usize];
+
+            // One operand spans the whole width so the block has no blank column inside
+            let full = rng.below(height as u64) as usize;
+            let rows: Vec<Vec<u8>> = (0..height)
+                .map(|y| {
+                    let len = if y == full {
+                        width
+                    } else {
+                        rng.between(1, width)
+                    };
+                    let mut row = vec![b' '; width];
+                    let start = if rng.below(2) == 0 { 0 } else { width - len };
+                    row[start] = b'1' + rng.below(9) as u8;
+                    for cell in &mut row[start + 1..start + len] {
+                        *cell = b'0' + rng.below(10) as u8;
+                    }
+                    row
+                })
+                .collect();
+
+            let row_operands: Vec<u128> = rows.iter().filter_map(|r| number(r.iter())).collect();
+            let column_operands: Vec<u128> = (0..width)
+                .filter_map(|x| number(rows.iter().map(|r| &r[x])))
+                .collect();
+            part1 = part1
+                .zip(apply(op, &row_operands))
+                .and_then(|(a, b)| a.checked_add(b));
+            part2 = part2
+                .zip(apply(op, &column_operands))
+                .and_then(|(a, b)| a.checked_add(b));
+
+            blocks.push((rows, op));
+        }
+
+        // Blocks side by side, separated by one or two blank columns, operators underneath
+        let height = blocks.iter().map(|(rows, _)| rows.len()).max().unwrap_or(0);
+        let mut lines = vec![Vec::new(); height + 1];
+        for (i, (rows, op)) in blocks.iter().enumerate() {
+            if i > 0 {
+                let gap = rng.between(1, 2);
+                for line in &mut lines {
+                    line.extend(std::iter::repeat_n(b' ', gap));
+                }
+            }
+            // Named operators may be wider than the digits, which leaves blank columns of digits
+            let width = rows[0].len().max(op.symbol().len());
+            for (y, line) in lines[..height].iter_mut().enumerate() {
+                let row = rows.get(y).map_or(&[][..], Vec::as_slice);
+                line.extend_from_slice(row);
+                line.extend(std::iter::repeat_n(b' ', width - row.len()));
+            }
+            let symbol = op.symbol().as_bytes();
+            lines[height].extend_from_slice(symbol);
+            lines[height].extend(std::iter::repeat_n(b' ', width - symbol.len()));
+        }
+
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(std::str::from_utf8(&line).expect("ASCII only"));
+            text.push('\n');
+        }
+        GeneratedWorksheet { text, part1, part2 }
+    }
+}
+
+impl Default for WorksheetGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The digits as a decimal number, skipping blanks, or `None` without any digit.
+fn number<'a, I: Iterator<Item = &'a u8>>(cells: I) -> Option<u128> {
+    cells.filter(|c| c.is_ascii_digit()).fold(None, |acc, &c| {
+        Some(acc.unwrap_or(0) * 10 + (c - b'0') as u128)
+    })
+}
+
+/// The result of a problem in plain `u128` arithmetic, kept apart from `Op::reduce`.
+fn apply(op: Op, operands: &[u128]) -> Option<u128> {
+    let (&first, rest) = operands.split_first()?;
+    rest.iter().try_fold(first, |acc, &v| match op {
+        Op::Add => acc.checked_add(v),
+        Op::Mul => acc.checked_mul(v),
+        Op::Sub => acc.checked_sub(v),
+        Op::Div => acc.checked_div(v),
+        Op::Max => Some(acc.max(v)),
+        Op::Min => Some(acc.min(v)),
+    })
+}
@@ -0,0 +1,482 @@
+use crate::adv_errors::{UpdateError, with_context};
+use std::fmt;
+use std::io::BufRead;
+use std::ops::Range;
+
+/// Integer types that problem results can be computed in.
+pub trait Number: Copy + Ord + fmt::Display + From<u64> {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+
+            fn checked_div(self, other: Self) -> Option<Self> {
+                <$t>::checked_div(self, other)
+            }
+        }
+    )*};
+}
+
+impl_number!(u64, u128);
+
+/// The operator under a problem, applied to all of its operands.
+///
+/// `-` and `/` are applied in reading order: the first operand minus (or divided by) each
+/// following one in turn. Division truncates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Mul,
+    Sub,
+    Div,
+    Max,
+    Min,
+}
+
+impl Op {
+    pub fn parse(s: &str) -> Result<Self, UpdateError> {
+        match s {
+            "+" => Ok(Op::Add),
+            "*" => Ok(Op::Mul),
+            "-" => Ok(Op::Sub),
+            "/" => Ok(Op::Div),
+            "max" => Ok(Op::Max),
+            "min" => Ok(Op::Min),
+            _ => Err(UpdateError::InvalidInput(format!(
+                "\"{}\" is not a valid operator!",
+                s
+            ))),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Mul => "*",
+            Op::Sub => "-",
+            Op::Div => "/",
+            Op::Max => "max",
+            Op::Min => "min",
+        }
+    }
+
+    /// Combines the operands in order, failing instead of overflowing.
+    pub fn reduce<T: Number>(&self, values: &[T]) -> Result<T, UpdateError> {
+        let overflow = || {
+            UpdateError::InvalidInput(format!(
+                "\"{}\" overflows {}!",
+                self.symbol(),
+                std::any::type_name::<T>()
+            ))
+        };
+
+        let (first, rest) = match (self, values.split_first()) {
+            (Op::Add, None) => return Ok(T::from(0)),
+            (Op::Mul, None) => return Ok(T::from(1)),
+            (_, None) => {
+                return Err(UpdateError::InvalidInput(format!(
+                    "\"{}\" needs at least one operand!",
+                    self.symbol()
+                )));
+            }
+            (_, Some((&first, rest))) => (first, rest),
+        };
+
+        rest.iter().try_fold(first, |acc, &v| match self {
+            Op::Add => acc.checked_add(v).ok_or_else(overflow),
+            Op::Mul => acc.checked_mul(v).ok_or_else(overflow),
+            Op::Sub => acc.checked_sub(v).ok_or_else(overflow),
+            Op::Div => acc
+                .checked_div(v)
+                .ok_or_else(|| UpdateError::InvalidInput("Division by zero!".into())),
+            Op::Max => Ok(acc.max(v)),
+            Op::Min => Ok(acc.min(v)),
+        })
+    }
+}
+
+/// Whether numbers are written along the rows or down the columns of a problem.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Rows,
+    Columns,
+}
+
+/// `Forward` is left-to-right along a row and top-to-bottom down a column.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+/// How the digits of a problem are read into operands: each row or column along `axis` is
+/// one number whose digits are read in `digits` order, and the numbers follow in `numbers`
+/// order across the other axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReadingOrder {
+    axis: Axis,
+    digits: Direction,
+    numbers: Direction,
+}
+
+impl ReadingOrder {
+    /// One number per row, rows from top to bottom (part 1).
+    pub const ROWS_LEFT_TO_RIGHT: Self =
+        Self::new(Axis::Rows, Direction::Forward, Direction::Forward);
+    /// One number per row, rows from bottom to top.
+    pub const ROWS_BOTTOM_TO_TOP: Self =
+        Self::new(Axis::Rows, Direction::Forward, Direction::Backward);
+    /// One number per column read top-to-bottom, columns from left to right (part 2).
+    pub const COLUMNS_TOP_TO_BOTTOM: Self =
+        Self::new(Axis::Columns, Direction::Forward, Direction::Forward);
+    /// One number per column read top-to-bottom, columns from right to left, as cephalopods do.
+    pub const COLUMNS_RIGHT_TO_LEFT: Self =
+        Self::new(Axis::Columns, Direction::Forward, Direction::Backward);
+
+    pub const fn new(axis: Axis, digits: Direction, numbers: Direction) -> Self {
+        ReadingOrder {
+            axis,
+            digits,
+            numbers,
+        }
+    }
+
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    pub fn digits(&self) -> Direction {
+        self.digits
+    }
+
+    pub fn numbers(&self) -> Direction {
+        self.numbers
+    }
+}
+
+/// A single problem of the worksheet: a block of columns with its operator underneath.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    span: Range<usize>,
+    op: Op,
+    digits: Vec<Vec<Option<u8>>>, // one row per input row, `None` for blanks
+    row_operands: Vec<u64>,
+    column_operands: Vec<u64>,
+}
+
+impl Problem {
+    /// The character columns of the problem, counted from 0 after expanding tabs.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn op(&self) -> Op {
+        self.op
+    }
+
+    /// The numbers read left-to-right, one per row, from top to bottom.
+    pub fn row_operands(&self) -> &[u64] {
+        &self.row_operands
+    }
+
+    /// The numbers read top-to-bottom, one per column, from left to right.
+    pub fn column_operands(&self) -> &[u64] {
+        &self.column_operands
+    }
+
+    /// The numbers read in the given order, skipping blanks. Rows or columns without digits
+    /// hold no number.
+    pub fn operands(&self, order: ReadingOrder) -> Result<Vec<u64>, UpdateError> {
+        let (height, width) = (self.digits.len(), self.span.len());
+        // Reading columns is reading the rows of the transposed block
+        let (count, len) = match order.axis {
+            Axis::Rows => (height, width),
+            Axis::Columns => (width, height),
+        };
+
+        let mut operands = Vec::with_capacity(count);
+        for i in 0..count {
+            let mut number: Option<u64> = None;
+            for j in 0..len {
+                let j = match order.digits {
+                    Direction::Forward => j,
+                    Direction::Backward => len - 1 - j,
+                };
+                let (y, x) = match order.axis {
+                    Axis::Rows => (i, j),
+                    Axis::Columns => (j, i),
+                };
+
+                let Some(digit) = self.digits[y][x] else {
+                    continue;
+                };
+                let value = number
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or_else(|| {
+                        let name = match order.axis {
+                            Axis::Rows => format!("Row {} of the problem", i + 1),
+                            Axis::Columns => format!("Column {}", self.span.start + i + 1),
+                        };
+                        self.locate(UpdateError::InvalidInput(format!(
+                            "{} overflows u64!",
+                            name
+                        )))
+                    })?;
+                number = Some(value);
+            }
+            operands.extend(number);
+        }
+
+        if order.numbers == Direction::Backward {
+            operands.reverse();
+        }
+        Ok(operands)
+    }
+
+    /// The result when the numbers are read in the given order, computed in `T`.
+    pub fn result_as<T: Number>(&self, order: ReadingOrder) -> Result<T, UpdateError> {
+        self.result(&self.operands(order)?)
+    }
+
+    /// The result when the numbers are read left-to-right (part 1).
+    pub fn row_result(&self) -> Result<u64, UpdateError> {
+        self.row_result_as()
+    }
+
+    /// The result when the numbers are read top-to-bottom (part 2).
+    pub fn column_result(&self) -> Result<u64, UpdateError> {
+        self.column_result_as()
+    }
+
+    /// Like `row_result`, computed in `T`, e.g. `u128` for results beyond `u64`.
+    pub fn row_result_as<T: Number>(&self) -> Result<T, UpdateError> {
+        self.result(&self.row_operands)
+    }
+
+    /// Like `column_result`, computed in `T`.
+    pub fn column_result_as<T: Number>(&self) -> Result<T, UpdateError> {
+        self.result(&self.column_operands)
+    }
+
+    fn result<T: Number>(&self, operands: &[u64]) -> Result<T, UpdateError> {
+        let operands: Vec<T> = operands.iter().map(|&v| T::from(v)).collect();
+        self.op.reduce(&operands).map_err(|e| self.locate(e))
+    }
+
+    /// Prefixes an error with the problem's columns.
+    fn locate(&self, err: UpdateError) -> UpdateError {
+        let (start, end) = (self.span.start + 1, self.span.end);
+        with_context(err, format_args!("Problem at columns {}-{}", start, end))
+    }
+}
+
+/// A parsed worksheet: the problems in order from left to right.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Worksheet {
+    problems: Vec<Problem>,
+}
+
+impl Worksheet {
+    /// Parses the rows of numbers followed by the operator row.
+    ///
+    /// Problems are separated by columns that are blank in every row. Tabs expand to the next
+    /// multiple of `TAB_WIDTH` columns, shorter lines read as if padded with blanks and blank
+    /// lines are skipped.
+    pub fn parse<R: BufRead>(reader: R) -> Result<Self, UpdateError> {
+        let mut rows: Vec<Row> = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let row = Row {
+                line: idx + 1,
+                chars: expand_tabs(line?.trim_end_matches('\r')),
+            };
+            if !row.chars.iter().all(|c| c.is_whitespace()) {
+                rows.push(row);
+            }
+        }
+        let ops = rows.pop().ok_or(UpdateError::EmptyInput)?;
+
+        let problems = segments(&rows, &ops)
+            .into_iter()
+            .map(|span| Self::parse_problem(&rows, &ops, span))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Worksheet { problems })
+    }
+
+    fn parse_problem(rows: &[Row], ops: &Row, span: Range<usize>) -> Result<Problem, UpdateError> {
+        let op_text = ops.text(span.clone());
+        let mut tokens = op_text.split_whitespace();
+        let op = match (tokens.next(), tokens.next()) {
+            (Some(token), None) => Op::parse(token).map_err(|e| ops.locate(e, &span))?,
+            _ => {
+                return Err(ops.locate(
+                    UpdateError::InvalidInput("Expected exactly one operator!".into()),
+                    &span,
+                ));
+            }
+        };
+
+        let mut digits = Vec::with_capacity(rows.len());
+        for row in rows {
+            let cells = span
+                .clone()
+                .map(|x| match row.chars.get(x) {
+                    None => Ok(None),
+                    Some(c) if c.is_whitespace() => Ok(None),
+                    Some(c) => c.to_digit(10).map(|d| Some(d as u8)).ok_or_else(|| {
+                        UpdateError::InvalidInput(format!(
+                            "Line {}, column {}: '{}' is not a digit!",
+                            row.line,
+                            x + 1,
+                            c
+                        ))
+                    }),
+                })
+                .collect::<Result<_, _>>()?;
+            digits.push(cells);
+        }
+
+        let mut problem = Problem {
+            span,
+            op,
+            digits,
+            row_operands: Vec::new(),
+            column_operands: Vec::new(),
+        };
+        problem.row_operands = problem.operands(ReadingOrder::ROWS_LEFT_TO_RIGHT)?;
+        problem.column_operands = problem.operands(ReadingOrder::COLUMNS_TOP_TO_BOTTOM)?;
+        Ok(problem)
+    }
+
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// The sum of all results when reading left-to-right (part 1).
+    pub fn row_total(&self) -> Result<u64, UpdateError> {
+        self.row_total_as()
+    }
+
+    /// The sum of all results when reading top-to-bottom (part 2).
+    pub fn column_total(&self) -> Result<u64, UpdateError> {
+        self.column_total_as()
+    }
+
+    /// Like `row_total`, computed in `T`.
+    pub fn row_total_as<T: Number>(&self) -> Result<T, UpdateError> {
+        let results = self.problems.iter().map(|p| p.row_result_as());
+        Self::total(results)
+    }
+
+    /// Like `column_total`, computed in `T`.
+    pub fn column_total_as<T: Number>(&self) -> Result<T, UpdateError> {
+        let results = self.problems.iter().map(|p| p.column_result_as());
+        Self::total(results)
+    }
+
+    /// The sum of all results when reading in the given order, computed in `T`.
+    pub fn total_as<T: Number>(&self, order: ReadingOrder) -> Result<T, UpdateError> {
+        let results = self.problems.iter().map(|p| p.result_as(order));
+        Self::total(results)
+    }
+
+    fn total<T: Number, I>(results: I) -> Result<T, UpdateError>
+    where
+        I: Iterator<Item = Result<T, UpdateError>>,
+    {
+        results.into_iter().try_fold(T::from(0), |acc, result| {
+            acc.checked_add(result?).ok_or_else(|| {
+                UpdateError::InvalidInput(format!(
+                    "The total overflows {}!",
+                    std::any::type_name::<T>()
+                ))
+            })
+        })
+    }
+}
+
+/// Tabs expand to the next multiple of this many columns.
+pub const TAB_WIDTH: usize = 8;
+
+/// One non-blank input line, with tabs expanded.
+struct Row {
+    line: usize,
+    chars: Vec<char>,
+}
+
+impl Row {
+    /// The characters in `span`, padded with blanks past the end of the line.
+    fn text(&self, span: Range<usize>) -> String {
+        span.map(|x| self.chars.get(x).copied().unwrap_or(' '))
+            .collect()
+    }
+
+    fn is_blank(&self, x: usize) -> bool {
+        self.chars.get(x).is_none_or(|c| c.is_whitespace())
+    }
+
+    /// Prefixes an error with the line and the columns of `span`.
+    fn locate(&self, err: UpdateError, span: &Range<usize>) -> UpdateError {
+        let (start, end) = (span.start + 1, span.end);
+        with_context(
+            err,
+            format_args!("Line {}, columns {}-{}", self.line, start, end),
+        )
+    }
+}
+
+fn expand_tabs(line: &str) -> Vec<char> {
+    let mut chars = Vec::with_capacity(line.len());
+    for c in line.chars() {
+        if c == '\t' {
+            let pad = TAB_WIDTH - chars.len() % TAB_WIDTH;
+            chars.extend(std::iter::repeat_n(' ', pad));
+        } else {
+            chars.push(c);
+        }
+    }
+    chars
+}
+
+/// The column spans of the problems: maximal runs of columns that are not blank in every row.
+fn segments(rows: &[Row], ops: &Row) -> Vec<Range<usize>> {
+    let width = rows
+        .iter()
+        .chain(std::iter::once(ops))
+        .map(|r| r.chars.len())
+        .max()
+        .unwrap_or(0);
+    let separator = |x: usize| ops.is_blank(x) && rows.iter().all(|r| r.is_blank(x));
+
+    let mut spans = Vec::new();
+    let mut start = None;
+    for x in 0..=width {
+        match (start, x == width || separator(x)) {
+            (None, false) => start = Some(x),
+            (Some(s), true) => {
+                spans.push(s..x);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans
+}
@@ -1,3 +1,6 @@
+pub mod render;
+pub mod trace;
+
 use crate::adv_errors::UpdateError;
 use std::collections::VecDeque;
 use std::io::BufRead;
@@ -9,51 +12,63 @@ pub fn solve<R: BufRead>(reader: R) -> Result<(u64, u64), UpdateError> {
         .map_err(|_| UpdateError::EmptyInput)?;
 
     let first_line = lines.pop_front().ok_or(UpdateError::EmptyInput)?;
-    let mut beam: Vec<u64> = first_line
-        .bytes()
+    let mut beam = source_beam(&first_line)?;
+
+    let mut count: u64 = 0;
+
+    for line in lines {
+        let (next_beam, splits) = split_beam(&beam, &line)?;
+        count += splits;
+        beam = next_beam;
+    }
+
+    let worlds: u64 = beam.iter().copied().sum();
+
+    Ok((count, worlds))
+}
+
+/// The number of timelines in each column of the first line: one below `S`, none below `.`.
+pub(crate) fn source_beam(line: &str) -> Result<Vec<u64>, UpdateError> {
+    line.bytes()
         .map(|b| match b {
             b'S' => Ok(1),
             b'.' => Ok(0),
-            _ => {
-                return Err(UpdateError::InvalidInput(format!(
-                    "The character \"{}\" is not defined for the first line!",
-                    b
-                )));
-            }
+            _ => Err(UpdateError::InvalidInput(format!(
+                "The character \"{}\" is not defined for the first line!",
+                b
+            ))),
         })
-        .collect::<Result<_, _>>()?;
+        .collect()
+}
+
+/// Passes the beam through one line, returning the beam below it and the number of
+/// splitters it hit.
+pub(crate) fn split_beam(beam: &[u64], line: &str) -> Result<(Vec<u64>, u64), UpdateError> {
     let length = beam.len();
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() != length {
+        return Err(UpdateError::InvalidInput(format!(
+            "Line has wrong length! Length is: {}, Should be: {}. For line: {}",
+            chars.len(),
+            length,
+            line
+        )));
+    }
 
-    let mut count: u64 = 0;
+    let mut count = 0;
+    let mut next_beam = beam.to_vec();
+    for (i, ch) in chars.into_iter().enumerate() {
+        if beam[i] > 0 && ch == '^' {
+            count += 1;
+            next_beam[i] = 0;
 
-    for line in lines {
-        let chars: Vec<char> = line.chars().collect();
-        if chars.len() != length {
-            return Err(UpdateError::InvalidInput(format!(
-                "Line has wrong length! Length is: {}, Should be: {}. For line: {}",
-                chars.len(),
-                length,
-                line
-            )));
-        }
-        let mut next_beam = beam.clone();
-        for (i, ch) in chars.into_iter().enumerate() {
-            if beam[i] > 0 && ch == '^' {
-                count += 1;
-                next_beam[i] = 0;
-
-                if i > 0 {
-                    next_beam[i - 1] += beam[i];
-                }
-                if i + 1 < length {
-                    next_beam[i + 1] += beam[i];
-                }
+            if i > 0 {
+                next_beam[i - 1] += beam[i];
+            }
+            if i + 1 < length {
+                next_beam[i + 1] += beam[i];
             }
         }
-        beam = next_beam;
     }
-
-    let worlds: u64 = beam.iter().map(|&x| x as u64).sum();
-
-    Ok((count, worlds))
+    Ok((next_beam, count))
 }
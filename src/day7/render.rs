@@ -0,0 +1,109 @@
+use crate::day7::trace::Trace;
+use std::fmt::Write;
+
+/// Side length of a cell in SVG pixels.
+const CELL: usize = 16;
+
+/// Renders the manifold like the puzzle illustration, with `|` wherever a beam passes.
+/// With `annotate`, each line with splitters ends in `column:timelines` for each of them,
+/// counting columns from 1.
+pub fn render_text(trace: &Trace, annotate: bool) -> String {
+    let mut out = String::with_capacity((trace.width() + 1) * trace.height());
+    for (row, beam) in trace.beams().iter().enumerate() {
+        out.extend((0..trace.width()).map(|col| match trace.cell(row, col) {
+            '.' if beam[col] > 0 => '|',
+            c => c,
+        }));
+
+        if annotate {
+            for col in 0..trace.width() {
+                if let Some(hits) = trace.hits(row, col) {
+                    write!(out, "  {}:{}", col + 1, hits).expect("writing to a String");
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders the manifold as an SVG image: beams are drawn as vertical strokes, wider where
+/// more timelines share them, and splitters as triangles, greyed out if no beam reaches them.
+/// With `annotate`, every splitter is labelled with the number of timelines hitting it.
+pub fn render_svg(trace: &Trace, annotate: bool) -> String {
+    let (width, height) = (trace.width() * CELL, trace.height() * CELL);
+    let mut out = String::new();
+    let mut svg = |line: String| {
+        out.push_str(&line);
+        out.push('\n');
+    };
+
+    svg(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    ));
+    svg(format!(
+        r##"<rect width="{}" height="{}" fill="#0f0f23"/>"##,
+        width, height
+    ));
+
+    let widest = trace.beams().iter().flatten().copied().max().unwrap_or(0);
+    for (row, beam) in trace.beams().iter().enumerate() {
+        let (top, middle) = (row * CELL, row * CELL + CELL / 2);
+        for (col, &timelines) in beam.iter().enumerate() {
+            let (left, center) = (col * CELL, col * CELL + CELL / 2);
+            match trace.cell(row, col) {
+                'S' => svg(format!(
+                    r##"<circle cx="{}" cy="{}" r="{}" fill="#ffff66"/>"##,
+                    center,
+                    middle,
+                    CELL / 3
+                )),
+                '^' => {
+                    let hits = trace.hits(row, col).unwrap_or(0);
+                    let fill = if hits > 0 { "#00cc00" } else { "#555555" };
+                    svg(format!(
+                        r#"<polygon points="{},{} {},{} {},{}" fill="{}"/>"#,
+                        center,
+                        top + 2,
+                        left + 2,
+                        top + CELL - 2,
+                        left + CELL - 2,
+                        top + CELL - 2,
+                        fill
+                    ));
+                    if annotate {
+                        svg(format!(
+                            r##"<text x="{}" y="{}" font-family="monospace" font-size="{}" text-anchor="middle" fill="#ffffff">{}</text>"##,
+                            center,
+                            top + CELL / 3,
+                            CELL / 2,
+                            hits
+                        ));
+                    }
+                }
+                _ if timelines > 0 => svg(format!(
+                    r##"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="#ffff66" stroke-width="{:.2}"/>"##,
+                    top,
+                    top + CELL,
+                    stroke_width(timelines, widest),
+                    x = center
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    svg("</svg>".to_string());
+    out
+}
+
+/// Between 1 and a third of a cell, growing with the logarithm of the timelines.
+fn stroke_width(timelines: u64, widest: u64) -> f64 {
+    let max = (CELL / 3) as f64;
+    if widest <= 1 {
+        return 1.0;
+    }
+    1.0 + (max - 1.0) * (timelines as f64).ln() / (widest as f64).ln()
+}
@@ -0,0 +1,69 @@
+use crate::adv_errors::UpdateError;
+use crate::day7::{source_beam, split_beam};
+use std::io::BufRead;
+
+/// The manifold together with the beam below every one of its lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    lines: Vec<Vec<char>>,
+    beams: Vec<Vec<u64>>, // timelines per column, after passing the line of the same index
+    splits: u64,
+}
+
+impl Trace {
+    /// Reads the manifold like `day7::solve`, keeping the beam of every line.
+    pub fn record<R: BufRead>(reader: R) -> Result<Self, UpdateError> {
+        let mut lines = reader.lines();
+        let first_line = lines.next().ok_or(UpdateError::EmptyInput)??;
+
+        let mut beams = vec![source_beam(&first_line)?];
+        let mut trace_lines = vec![first_line.chars().collect()];
+        let mut splits = 0;
+        for line in lines {
+            let line = line?;
+            let (beam, count) = split_beam(beams.last().expect("source beam"), &line)?;
+            splits += count;
+            beams.push(beam);
+            trace_lines.push(line.chars().collect());
+        }
+
+        Ok(Trace {
+            lines: trace_lines,
+            beams,
+            splits,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.beams[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The input character at a position.
+    pub fn cell(&self, row: usize, col: usize) -> char {
+        self.lines[row][col]
+    }
+
+    /// The number of timelines in each column below every line, starting with the source.
+    pub fn beams(&self) -> &[Vec<u64>] {
+        &self.beams
+    }
+
+    /// The number of timelines reaching the splitter at a position, `None` without a splitter.
+    pub fn hits(&self, row: usize, col: usize) -> Option<u64> {
+        (row > 0 && self.lines[row][col] == '^').then(|| self.beams[row - 1][col])
+    }
+
+    /// The number of splitters hit by at least one timeline (part 1).
+    pub fn splits(&self) -> u64 {
+        self.splits
+    }
+
+    /// The number of timelines leaving the manifold (part 2).
+    pub fn worlds(&self) -> u64 {
+        self.beams.last().map_or(0, |b| b.iter().sum())
+    }
+}
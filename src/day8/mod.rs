@@ -56,7 +56,7 @@ pub fn read_points<R: BufRead>(reader: R) -> Result<Vec<Point>, UpdateError> {
     let points: Vec<Point> = reader
         .lines()
         .map(|line| {
-            let line = line.map_err(|e| UpdateError::Io(e))?;
+            let line = line.map_err(UpdateError::Io)?;
             let line = line.trim();
             if line.is_empty() {
                 return Err(UpdateError::EmptyInput); // we’ll filter empty later
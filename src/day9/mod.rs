@@ -61,7 +61,7 @@ fn read_points<R: BufRead>(reader: R) -> Result<Vec<Point>, UpdateError> {
     let points: Vec<Point> = reader
         .lines()
         .map(|line| {
-            let line = line.map_err(|e| UpdateError::Io(e))?;
+            let line = line.map_err(UpdateError::Io)?;
             let line = line.trim();
             if line.is_empty() {
                 return Err(UpdateError::EmptyInput); // we’ll filter empty later
@@ -100,9 +100,11 @@ fn read_points<R: BufRead>(reader: R) -> Result<Vec<Point>, UpdateError> {
     Ok(points)
 }
 
+type Compressed = (Vec<u32>, Vec<u32>, HashMap<u32, usize>, HashMap<u32, usize>);
+
 /// Coordinate compression: maps original x/y coordinates to small indices for grid usage.
 /// Returns unique x/y lists and maps from original coordinate -> compressed index.
-fn compress(points: &[Point]) -> (Vec<u32>, Vec<u32>, HashMap<u32, usize>, HashMap<u32, usize>) {
+fn compress(points: &[Point]) -> Compressed {
     let mut xs: Vec<u32> = points.iter().map(|p| p.x).collect();
     let mut ys: Vec<u32> = points.iter().map(|p| p.y).collect();
 
@@ -201,20 +203,16 @@ fn is_rect_inside(
     let y2 = *y_map.get(&rect.y2).unwrap_or(&0) as u32;
     for edge in h_edges {
         // Horizontal edges
-        if edge.height > y1 && edge.height < y2 {
-            if edge.end > x1 && edge.start < x2 {
-                // Edge crosses rectangle horizontally
-                return false;
-            }
+        if edge.height > y1 && edge.height < y2 && edge.end > x1 && edge.start < x2 {
+            // Edge crosses rectangle horizontally
+            return false;
         }
     }
     for edge in v_edges {
         // Vertical edges
-        if edge.height > x1 && edge.height < x2 {
-            if edge.end > y1 && edge.start < y2 {
-                // Edge crosses rectangle vertically
-                return false;
-            }
+        if edge.height > x1 && edge.height < x2 && edge.end > y1 && edge.start < y2 {
+            // Edge crosses rectangle vertically
+            return false;
         }
     }
     true
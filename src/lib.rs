@@ -8,3 +8,4 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod rng;
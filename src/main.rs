@@ -1,29 +1,25 @@
-mod adv_errors;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use advent_of_code_2025::adv_errors::UpdateError;
+use advent_of_code_2025::{day3, day5, day6, day7, day8, day9};
 
-use adv_errors::UpdateError;
+use advent_of_code_2025::day1::sm::Day1StateMachine;
 
-use day1::sm::Day1StateMachine;
+use advent_of_code_2025::day2::accumulator::Day2Accumulator;
 
-use day2::accumulator::Day2Accumulator;
+use advent_of_code_2025::day3::accumulator::Day3Accumulator;
 
-use day3::accumulator::Day3Accumulator;
+use advent_of_code_2025::day4::bands::BandSolver;
+use advent_of_code_2025::day4::render::{self, FrameFormat};
+use advent_of_code_2025::day4::solver::Day4Solver;
 
-use day4::solver::Day4Solver;
+use advent_of_code_2025::day5::audit::Auditor;
+
+use advent_of_code_2025::day7::trace::Trace;
 
 use clap::{Parser, ValueEnum};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
-use std::path::PathBuf;
-use std::time::Instant;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(name = "advent_of_code_2025")]
@@ -34,6 +30,58 @@ struct Args {
 
     #[arg(short, long, default_value = "day%ninput")]
     input: PathBuf,
+
+    /// Print a detailed per-item report (day 3)
+    #[arg(long)]
+    report: bool,
+
+    /// Export the per-item report as CSV to this file (day 3)
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    /// Render every generation of the grid (day 4)
+    #[arg(long, value_enum)]
+    render: Option<Render>,
+
+    /// Directory for rendered frames (day 4)
+    #[arg(long, default_value = "day4_frames")]
+    frames: PathBuf,
+
+    /// List why each ingredient is fresh or spoiled (day 5)
+    #[arg(long)]
+    audit: bool,
+
+    /// Report how the ranges merge, joining adjacent ones (day 5)
+    #[arg(long)]
+    merge_stats: bool,
+
+    /// Compute the worksheet results as u128 instead of u64 (day 6)
+    #[arg(long)]
+    wide: bool,
+
+    /// Stream the grid in bands of this many rows through temporary files (day 4)
+    #[arg(long)]
+    bands: Option<usize>,
+
+    /// Print the manifold with the beams drawn in (day 7)
+    #[arg(long)]
+    trace: bool,
+
+    /// Draw the manifold with its beams as SVG into this file (day 7)
+    #[arg(long)]
+    svg: Option<PathBuf>,
+
+    /// Label every splitter with the number of timelines hitting it (day 7)
+    #[arg(long)]
+    annotate: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum Render {
+    Text,
+    Pgm,
+    Ppm,
+    Animate,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
@@ -53,22 +101,23 @@ enum Mode {
     //Day12,
 }
 
+type DayFn = fn(&PathBuf, &Args) -> Result<(), UpdateError>;
+
+/// Ingredients classified in parallel at a time (day 5)
+const DAY5_BATCH_SIZE: usize = 1 << 16;
+
 // tiny helper to wrap timing around any day function
-fn time_day(
-    name: &str,
-    f: fn(&PathBuf) -> Result<(), UpdateError>,
-    input: &PathBuf,
-) -> Result<(), UpdateError> {
+fn time_day(name: &str, f: DayFn, input: &PathBuf, args: &Args) -> Result<(), UpdateError> {
     println!("=== {} start ===", name);
     let start = Instant::now();
-    let result = f(input);
+    let result = f(input, args);
     let elapsed = start.elapsed();
     println!("Elapsed: {:.3?}", elapsed);
     println!("===  {} end  ===\n", name);
     result
 }
 
-fn day_input_path(pattern: &PathBuf, day_name: &str) -> PathBuf {
+fn day_input_path(pattern: &Path, day_name: &str) -> PathBuf {
     let s = pattern.to_string_lossy();
 
     if s.contains("%n") {
@@ -76,11 +125,11 @@ fn day_input_path(pattern: &PathBuf, day_name: &str) -> PathBuf {
         let day_number = format!("{:0>2}", day_number.parse::<u32>().unwrap());
         PathBuf::from(s.replace("%n", &day_number))
     } else {
-        pattern.clone()
+        pattern.to_path_buf()
     }
 }
 
-fn day1(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day1(input: &std::path::PathBuf, _args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
@@ -96,10 +145,10 @@ fn day1(input: &std::path::PathBuf) -> Result<(), UpdateError> {
     Ok(())
 }
 
-fn day2(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day2(input: &std::path::PathBuf, _args: &Args) -> Result<(), UpdateError> {
     let file = File::open(input)?;
     let mut reader = BufReader::new(file);
-    let mut acc = Day2Accumulator::new();
+    let mut acc = Day2Accumulator::with_merge();
 
     let mut buffer = [0u8; 8192]; // 8KB buffer
     let mut leftover = String::new(); // stores partial ranges across buffer boundaries
@@ -121,10 +170,10 @@ fn day2(input: &std::path::PathBuf) -> Result<(), UpdateError> {
                 leftover.clear();
 
                 let trimmed = range_str.trim();
-                if !trimmed.is_empty() {
-                    if let Err(e) = acc.update(trimmed) {
-                        eprintln!("Failed to process '{}': {}", trimmed, e);
-                    }
+                if !trimmed.is_empty()
+                    && let Err(e) = acc.update(trimmed)
+                {
+                    eprintln!("Failed to process '{}': {}", trimmed, e);
                 }
 
                 start = i + c.len_utf8();
@@ -137,29 +186,45 @@ fn day2(input: &std::path::PathBuf) -> Result<(), UpdateError> {
 
     // process any remaining piece after the last comma
     let trimmed = leftover.trim();
-    if !trimmed.is_empty() {
-        if let Err(e) = acc.update(trimmed) {
-            eprintln!("Failed to process '{}': {}", trimmed, e);
-        }
+    if !trimmed.is_empty()
+        && let Err(e) = acc.update(trimmed)
+    {
+        eprintln!("Failed to process '{}': {}", trimmed, e);
     }
 
+    let overlaps = acc.finalize_input();
+
     println!("Total sum of doubled numbers: {}", acc.get_sum_part1());
     println!("Total sum of all repeated numbers: {}", acc.get_sum_part2());
+
+    if !overlaps.is_empty() {
+        for (a, b) in &overlaps {
+            eprintln!("Warning: ranges {} and {} overlap", a, b);
+        }
+        println!(
+            "Total sum of doubled numbers without duplicates: {}",
+            acc.get_dedup_sum_part1()
+        );
+        println!(
+            "Total sum of all repeated numbers without duplicates: {}",
+            acc.get_dedup_sum_part2()
+        );
+    }
     Ok(())
 }
 
-fn day3(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day3(input: &std::path::PathBuf, args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
-    let mut acc = Day3Accumulator::new();
+    let mut acc = if args.report || args.csv.is_some() {
+        Day3Accumulator::new().with_reports()
+    } else {
+        Day3Accumulator::new()
+    };
+
+    acc.update_reader(reader)?;
 
-    for line in reader.lines() {
-        let line = line?;
-        if let Err(e) = acc.update(&line) {
-            eprintln!("Failed to process '{}': {}", line, e);
-        };
-    }
     println!(
         "Maximum joltage for part 1 is: {}",
         acc.get_total_joltage_part1()
@@ -168,53 +233,118 @@ fn day3(input: &std::path::PathBuf) -> Result<(), UpdateError> {
         "Maximum joltage for part 2 is: {}",
         acc.get_total_joltage_part2()
     );
+
+    if args.report {
+        for report in acc.get_reports() {
+            println!(
+                "{:>5} part 1: {} -> {}",
+                report.line(),
+                day3::report::highlight(report.bank(), report.part1()),
+                report.part1().to_decimal_string()
+            );
+            println!(
+                "{:>5} part 2: {} -> {}",
+                "",
+                day3::report::highlight(report.bank(), report.part2()),
+                report.part2().to_decimal_string()
+            );
+        }
+    }
+
+    if let Some(csv) = &args.csv {
+        day3::report::write_csv(acc.get_reports(), BufWriter::new(File::create(csv)?))?;
+    }
     Ok(())
 }
 
-fn day4(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day4(input: &std::path::PathBuf, args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
+    if let Some(rows) = args.bands {
+        let history = BandSolver::new()
+            .with_band_rows(rows)
+            .run(reader, io::sink(), None)?;
+        println!(
+            "The number of accessible crates after the first step is: {}",
+            history.first().map(|g| g.removed()).unwrap_or(0)
+        );
+        let total_movable: u64 = history.iter().map(|g| g.removed()).sum();
+        println!("The total number of movable crates is: {}", total_movable);
+        return Ok(());
+    }
+
     let mut solver = Day4Solver::default();
 
     for line in reader.lines() {
-        let line = line?;
-        if let Err(e) = solver.add_row(&line) {
-            eprintln!("Failed to process '{}': {}", line, e);
-        };
+        solver.add_row(&line?)?;
     }
 
-    solver.finalize_input();
+    solver.finalize_input()?;
 
-    let mut firstloop = true;
-    let mut total_movable = 0;
-    loop {
-        let accessible = solver.solve().map_err(|e| {
-            eprintln!("Solver failed: {:?}", e);
-            io::Error::new(io::ErrorKind::Other, "Solver failed")
-        })?;
-        if firstloop {
-            println!(
-                "The number of accessible crates after the first step is: {}",
-                accessible
-            );
-            firstloop = false;
+    let history = match args.render {
+        None => solver.run_until_stable(None)?,
+        Some(Render::Animate) => {
+            render::animate(&mut solver, io::stdout(), Duration::from_millis(100), None)?
         }
-        if accessible == 0 {
-            break;
+        Some(Render::Text) => {
+            render::export_generations(&mut solver, FrameFormat::Text, &args.frames, None)?
         }
-        total_movable += accessible;
-    }
+        Some(Render::Pgm) => {
+            render::export_generations(&mut solver, FrameFormat::Pgm, &args.frames, None)?
+        }
+        Some(Render::Ppm) => {
+            render::export_generations(&mut solver, FrameFormat::Ppm, &args.frames, None)?
+        }
+    };
+    println!(
+        "The number of accessible crates after the first step is: {}",
+        history.first().map(|g| g.count()).unwrap_or(0)
+    );
+
+    let total_movable: u64 = history.iter().map(|g| g.count()).sum();
     println!("The total number of movable crates is: {}", total_movable);
 
     Ok(())
 }
 
-fn day5(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day5(input: &std::path::PathBuf, args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
-    let (num_fresh, total_ranges) = day5::solve(reader).unwrap();
+    // Ingredients are streamed, only the ranges are held in memory
+    let mut input = day5::InputReader::new(reader);
+    let ranges = input.read_ranges::<i128>()?;
+
+    if args.merge_stats {
+        let (_, stats) = day5::merge_ranges_with(ranges.clone(), day5::MergeMode::Adjacent);
+        println!(
+            "{} ranges merge into {} ({} overlapping, {} adjacent)",
+            stats.input_count(),
+            stats.merged_count(),
+            stats.overlaps(),
+            stats.adjacent()
+        );
+        match stats.largest_gap() {
+            Some(gap) => println!("The largest gap between ranges is: {}", gap),
+            None => println!("There are no gaps between ranges"),
+        }
+    }
+
+    let (num_fresh, total_ranges) = if args.audit {
+        let auditor = Auditor::new(&ranges);
+        let mut num_fresh = 0;
+        for ingredient in input.ingredients() {
+            let record = auditor.audit(ingredient?);
+            if record.is_fresh() {
+                num_fresh += 1;
+            }
+            println!("{}", record);
+        }
+        (num_fresh, day5::total_coverage(auditor.fresh_ranges())?)
+    } else {
+        day5::solve_stream(ranges, &mut input, Some(DAY5_BATCH_SIZE))?
+    };
 
     println!("The number of fresh ingredients is: {}", num_fresh);
     println!(
@@ -224,11 +354,16 @@ fn day5(input: &std::path::PathBuf) -> Result<(), UpdateError> {
     Ok(())
 }
 
-fn day6(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day6(input: &std::path::PathBuf, args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
-    let (lr_sum, td_sum) = day6::solve(reader).unwrap();
+    let (lr_sum, td_sum) = if args.wide {
+        day6::solve_as::<u128, _>(reader)?
+    } else {
+        let (lr_sum, td_sum) = day6::solve(reader)?;
+        (lr_sum as u128, td_sum as u128)
+    };
 
     println!(
         "The sum of the solutions to all the problems when read horizontally is: {}",
@@ -243,11 +378,22 @@ fn day6(input: &std::path::PathBuf) -> Result<(), UpdateError> {
     Ok(())
 }
 
-fn day7(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day7(input: &std::path::PathBuf, args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
-    let (splits, worlds) = day7::solve(reader).unwrap();
+    let (splits, worlds) = if args.trace || args.svg.is_some() {
+        let trace = Trace::record(reader)?;
+        if args.trace {
+            print!("{}", day7::render::render_text(&trace, args.annotate));
+        }
+        if let Some(path) = &args.svg {
+            fs::write(path, day7::render::render_svg(&trace, args.annotate))?;
+        }
+        (trace.splits(), trace.worlds())
+    } else {
+        day7::solve(reader)?
+    };
 
     println!("The number of splits is: {}", splits);
 
@@ -256,7 +402,7 @@ fn day7(input: &std::path::PathBuf) -> Result<(), UpdateError> {
     Ok(())
 }
 
-fn day8(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day8(input: &std::path::PathBuf, _args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
@@ -275,7 +421,7 @@ fn day8(input: &std::path::PathBuf) -> Result<(), UpdateError> {
     Ok(())
 }
 
-fn day9(input: &std::path::PathBuf) -> Result<(), UpdateError> {
+fn day9(input: &std::path::PathBuf, _args: &Args) -> Result<(), UpdateError> {
     let input = File::open(input)?;
     let reader = BufReader::new(input);
 
@@ -294,7 +440,7 @@ fn day9(input: &std::path::PathBuf) -> Result<(), UpdateError> {
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    let day_map: &[(&str, Mode, fn(&PathBuf) -> Result<(), UpdateError>)] = &[
+    let day_map: &[(&str, Mode, DayFn)] = &[
         ("Day1", Mode::Day1, day1),
         ("Day2", Mode::Day2, day2),
         ("Day3", Mode::Day3, day3),
@@ -314,7 +460,7 @@ fn main() -> io::Result<()> {
                     eprintln!("Input file {:?} does not exist, skipping", day_input);
                     continue;
                 }
-                if let Err(e) = time_day(name, *func, &day_input) {
+                if let Err(e) = time_day(name, *func, &day_input, &args) {
                     match e {
                         UpdateError::EmptyInput => eprintln!("{} failed: input was empty!", name),
                         UpdateError::InvalidInput(msg) => {
@@ -330,7 +476,7 @@ fn main() -> io::Result<()> {
         _ => {
             if let Some((name, _, func)) = day_map.iter().find(|(_, m, _)| *m == args.mode) {
                 let day_input = day_input_path(&args.input, name);
-                if let Err(e) = time_day(name, *func, &day_input) {
+                if let Err(e) = time_day(name, *func, &day_input, &args) {
                     match e {
                         UpdateError::EmptyInput => eprintln!("{} failed: input was empty!", name),
                         UpdateError::InvalidInput(msg) => {
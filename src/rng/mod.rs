@@ -0,0 +1,39 @@
+/// A small xorshift generator, so generated inputs are reproducible without extra dependencies.
+#[derive(Clone, Debug)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+    /// Any seed but zero, which would only ever produce zeros and is replaced by `DEFAULT_SEED`.
+    pub fn new(seed: u64) -> Self {
+        XorShift {
+            state: if seed == 0 { Self::DEFAULT_SEED } else { seed },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A value in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// A value in `lower..=upper`.
+    pub fn between(&mut self, lower: usize, upper: usize) -> usize {
+        lower + self.below((upper - lower + 1) as u64) as usize
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SEED)
+    }
+}
@@ -176,3 +176,32 @@ fn test_example() {
             + 2121212121
     );
 }
+
+#[test]
+fn test_overlapping_ranges_merged() {
+    let mut acc = Day2Accumulator::with_merge();
+    acc.update("11-33").unwrap();
+    acc.update("22-44").unwrap();
+    acc.update("95-115").unwrap();
+
+    let overlaps = acc.finalize_input();
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].0.to_string(), "11-33");
+    assert_eq!(overlaps[0].1.to_string(), "22-44");
+
+    // 22 and 33 are counted twice without merging
+    assert_eq!(acc.get_sum_part1(), 11 + 22 + 33 + 22 + 33 + 44 + 99);
+    assert_eq!(acc.get_dedup_sum_part1(), 11 + 22 + 33 + 44 + 99);
+    assert_eq!(acc.get_dedup_sum_part2(), 11 + 22 + 33 + 44 + 99 + 111);
+}
+
+#[test]
+fn test_disjoint_ranges_merged() {
+    let mut acc = Day2Accumulator::with_merge();
+    acc.update("11-22").unwrap();
+    acc.update("95-115").unwrap();
+
+    assert!(acc.finalize_input().is_empty());
+    assert_eq!(acc.get_dedup_sum_part1(), acc.get_sum_part1());
+    assert_eq!(acc.get_dedup_sum_part2(), acc.get_sum_part2());
+}
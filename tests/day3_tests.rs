@@ -1,10 +1,13 @@
+use advent_of_code_2025::adv_errors::UpdateError;
 use advent_of_code_2025::day3::accumulator::Day3Accumulator;
+use advent_of_code_2025::day3::report::{highlight, write_csv};
+use advent_of_code_2025::day3::selection::{largest_k_digits, smallest_k_digits};
 
 #[test]
 fn test_case_example() {
     let mut acc = Day3Accumulator::new();
-    let mut total_joltage_part1: u64 = 0;
-    let mut total_joltage_part2: u64 = 0;
+    let mut total_joltage_part1: u128 = 0;
+    let mut total_joltage_part2: u128 = 0;
 
     acc.update("987654321111111").unwrap();
     total_joltage_part1 += 98;
@@ -27,3 +30,186 @@ fn test_case_example() {
     total_joltage_part2 += 888911112111;
     assert_eq!(acc.get_total_joltage_part2(), total_joltage_part2);
 }
+
+#[test]
+fn test_custom_k() {
+    let mut acc = Day3Accumulator::with_k(3, 25);
+    acc.update("9876543211111119876543211111").unwrap();
+    assert_eq!(acc.get_total_joltage_part1(), 998);
+    assert_eq!(acc.get_total_joltage_part2(), 9876543211119876543211111);
+}
+
+#[test]
+fn test_selection_positions() {
+    let selection = largest_k_digits("818181911112111", 3).unwrap();
+    assert_eq!(selection.digits(), &[9, 2, 1]);
+    assert_eq!(selection.positions(), &[6, 11, 12]);
+    assert_eq!(selection.to_u128(), Some(921));
+    assert_eq!(selection.to_decimal_string(), "921");
+}
+
+#[test]
+fn test_smallest_k_digits() {
+    let selection = smallest_k_digits("818181911112111", 4).unwrap();
+    assert_eq!(selection.to_decimal_string(), "1111");
+    assert_eq!(selection.positions(), &[1, 3, 5, 7]);
+}
+
+#[test]
+fn test_selection_wider_than_u128() {
+    let line = "9".repeat(45);
+    let selection = largest_k_digits(&line, 40).unwrap();
+    assert_eq!(selection.to_u128(), None);
+    assert_eq!(selection.to_decimal_string(), "9".repeat(40));
+
+    let mut acc = Day3Accumulator::with_k(2, 40);
+    assert!(acc.update(&line).is_err());
+}
+
+#[test]
+fn test_crlf_and_whitespace_skipped() {
+    let mut acc = Day3Accumulator::new();
+    acc.update("987654321111111\r").unwrap();
+    acc.update(" 811111111111119 ").unwrap();
+    assert_eq!(acc.get_total_joltage_part1(), 98 + 89);
+    assert_eq!(acc.get_total_joltage_part2(), 987654321111 + 811111111119);
+
+    // Positions still refer to the original line
+    let selection = largest_k_digits(" 19", 1).unwrap();
+    assert_eq!(selection.positions(), &[2]);
+}
+
+#[test]
+fn test_invalid_character() {
+    let mut acc = Day3Accumulator::new();
+    acc.update("987654321111111").unwrap();
+    let err = acc.update("98765x321111111").unwrap_err();
+    assert!(matches!(
+        err,
+        UpdateError::InvalidInput(msg) if msg.contains("Line 2") && msg.contains("'x' at position 5")
+    ));
+
+    assert!(largest_k_digits("12٣45", 2).is_err());
+    assert_eq!(acc.get_total_joltage_part1(), 98);
+}
+
+#[test]
+fn test_line_shorter_than_k() {
+    let err = largest_k_digits("12345", 6).unwrap_err();
+    assert!(matches!(
+        err,
+        UpdateError::InvalidInput(msg) if msg.contains("only 5 batteries")
+    ));
+
+    let mut acc = Day3Accumulator::new();
+    assert!(acc.update("98765432111").is_err());
+    assert_eq!(acc.get_total_joltage_part1(), 0);
+    assert_eq!(acc.get_total_joltage_part2(), 0);
+
+    // Exactly k digits selects every battery
+    let selection = largest_k_digits("1234", 4).unwrap();
+    assert_eq!(selection.to_u128(), Some(1234));
+}
+
+#[test]
+fn test_empty_input() {
+    let mut acc = Day3Accumulator::new();
+    assert!(matches!(
+        acc.update("").unwrap_err(),
+        UpdateError::EmptyInput
+    ));
+    assert!(matches!(
+        acc.update("\r").unwrap_err(),
+        UpdateError::EmptyInput
+    ));
+}
+
+#[test]
+fn test_bank_reports() {
+    let mut acc = Day3Accumulator::new().with_reports();
+    acc.update("987654321111111").unwrap();
+    acc.update("818181911112111").unwrap();
+
+    let reports = acc.get_reports();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[1].line(), 2);
+    assert_eq!(reports[1].part1().positions(), &[6, 11]);
+    assert_eq!(reports[1].part1().to_decimal_string(), "92");
+    assert_eq!(reports[1].part2().to_decimal_string(), "888911112111");
+
+    assert_eq!(
+        highlight(reports[0].bank(), reports[0].part1()),
+        "\x1b[1;32m9\x1b[0m\x1b[1;32m8\x1b[0m7654321111111"
+    );
+
+    let mut csv = Vec::new();
+    write_csv(&reports[..1], &mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "line,part1_positions,part1_joltage,part2_positions,part2_joltage\n\
+         1,0 1,98,0 1 2 3 4 5 6 7 8 9 10 11,987654321111\n"
+    );
+
+    // Reports are only collected on request
+    let mut acc = Day3Accumulator::new();
+    acc.update("987654321111111").unwrap();
+    assert!(acc.get_reports().is_empty());
+}
+
+#[test]
+fn test_batch_matches_line_by_line() {
+    let input = "987654321111111\n811111111111119\n\n234234234234278\r\n818181911112111\n";
+
+    let mut sequential = Day3Accumulator::new();
+    for line in input.lines().filter(|l| !l.is_empty()) {
+        sequential.update(line).unwrap();
+    }
+
+    let mut batch = Day3Accumulator::new().with_reports();
+    batch.update_all(input).unwrap();
+    assert_eq!(batch.get_total_joltage_part1(), 357);
+    assert_eq!(batch.get_total_joltage_part2(), 3121910778619);
+    assert_eq!(
+        batch.get_total_joltage_part1(),
+        sequential.get_total_joltage_part1()
+    );
+    assert_eq!(
+        batch.get_total_joltage_part2(),
+        sequential.get_total_joltage_part2()
+    );
+
+    let lines: Vec<usize> = batch.get_reports().iter().map(|r| r.line()).collect();
+    assert_eq!(lines, vec![1, 2, 4, 5]);
+
+    let mut reader = Day3Accumulator::new();
+    reader.update_reader(input.as_bytes()).unwrap();
+    assert_eq!(reader.get_total_joltage_part2(), 3121910778619);
+}
+
+#[test]
+fn test_batch_reports_first_invalid_line() {
+    let mut acc = Day3Accumulator::new().with_reports();
+    let err = acc
+        .update_all("987654321111111\n12x4567890123\n1\n")
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        UpdateError::InvalidInput(msg) if msg.starts_with("Line 2")
+    ));
+
+    // Later workers fail long before the one starting with a long bank reaches line 2,
+    // yet the first invalid bank in input order is reported
+    let long_bank = format!("{}\n", "9".repeat(200_000));
+    let input: String = std::iter::once(long_bank.as_str())
+        .chain(std::iter::repeat_n("12x4567890123\n", 1000))
+        .collect();
+    let err = Day3Accumulator::new().update_all(&input).unwrap_err();
+    assert!(err.to_string().contains("Line 2:"), "{}", err);
+
+    // The failed batch left nothing behind, not even the valid bank before the bad one
+    assert_eq!(acc.get_total_joltage_part1(), 0);
+    assert_eq!(acc.get_total_joltage_part2(), 0);
+    assert!(acc.get_reports().is_empty());
+    acc.update_all("987654321111111\n").unwrap();
+    assert_eq!(acc.get_reports()[0].line(), 1);
+}
@@ -1,9 +1,15 @@
-use advent_of_code_2025::day4::solver::Day4Solver;
+use advent_of_code_2025::adv_errors::UpdateError;
+use advent_of_code_2025::day4::bands::BandSolver;
+use advent_of_code_2025::day4::kernel::{Boundary, Kernel};
+use advent_of_code_2025::day4::render::{
+    FrameFormat, export_generations, render_text, write_pgm, write_ppm,
+};
+use advent_of_code_2025::day4::rules::Rule;
+use advent_of_code_2025::day4::solver::{Backend, Day4Solver};
+use advent_of_code_2025::rng::XorShift;
+use std::io;
 
-#[test]
-fn test_case_example() {
-    let mut solver = Day4Solver::default();
-    let input = "\
+const EXAMPLE: &str = "\
 ..@@.@@@@.
 @@@.@.@.@@
 @@@@@.@.@@
@@ -15,10 +21,610 @@ fn test_case_example() {
 .@@@@@@@@.
 @.@.@@@.@.";
 
-    for line in input.lines() {
-        solver.add_row(&line).expect("Failed to add row");
+fn example_solver() -> Day4Solver {
+    let mut solver = Day4Solver::default();
+    for line in EXAMPLE.lines() {
+        solver.add_row(line).expect("Failed to add row");
     }
-    solver.finalize_input();
+    solver.finalize_input().expect("Empty grid");
+    solver
+}
+
+#[test]
+fn test_case_example() {
+    let mut solver = example_solver();
 
     assert_eq!(solver.solve().expect("Solver failed"), 13);
 }
+
+#[test]
+fn test_run_until_stable() {
+    let mut solver = example_solver();
+    let history = solver.run_until_stable(None).expect("Solver failed");
+
+    assert_eq!(history[0].count(), 13);
+    assert_eq!(history.iter().map(|g| g.count()).sum::<u64>(), 43);
+    assert!(history.iter().all(|g| g.count() > 0));
+
+    // The top row loses the crates at x = 2, 3, 5, 6 and 8 in the first generation
+    let mut top_row: Vec<usize> = history[0]
+        .removed()
+        .iter()
+        .filter(|&&(_, y)| y == 0)
+        .map(|&(x, _)| x)
+        .collect();
+    top_row.sort();
+    assert_eq!(top_row, vec![2, 3, 5, 6, 8]);
+
+    // A stable grid stays stable
+    assert!(solver.run_until_stable(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_generation_limit() {
+    let mut solver = example_solver();
+    let history = solver.run_until_stable(Some(2)).expect("Solver failed");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].count(), 13);
+
+    let rest = solver.run_until_stable(None).expect("Solver failed");
+    let total: u64 = history.iter().chain(rest.iter()).map(|g| g.count()).sum();
+    assert_eq!(total, 43);
+}
+
+#[test]
+fn test_render_text() {
+    let mut solver = example_solver();
+    assert_eq!(
+        render_text(&solver).replace('\n', ""),
+        EXAMPLE.replace('\n', "")
+    );
+
+    solver.solve().expect("Solver failed");
+    let rendered = render_text(&solver);
+    assert_eq!(rendered.lines().next(), Some("..xx.xx@x."));
+    assert_eq!(rendered.matches('x').count(), 13);
+}
+
+#[test]
+fn test_write_images() {
+    let mut solver = example_solver();
+    solver.solve().expect("Solver failed");
+
+    let mut pgm = Vec::new();
+    write_pgm(&solver, &mut pgm).unwrap();
+    assert!(pgm.starts_with(b"P5\n10 10\n255\n"));
+    assert_eq!(pgm.len(), "P5\n10 10\n255\n".len() + 100);
+    assert_eq!(pgm.iter().filter(|&&p| p == 128).count(), 13);
+
+    let mut ppm = Vec::new();
+    write_ppm(&solver, &mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+    assert_eq!(ppm.len(), "P6\n10 10\n255\n".len() + 300);
+}
+
+#[test]
+fn test_export_generations() {
+    let dir = std::env::temp_dir().join(format!("day4_frames_{}", std::process::id()));
+    let mut solver = example_solver();
+    let history = export_generations(&mut solver, FrameFormat::Text, &dir, None).unwrap();
+
+    let frames = std::fs::read_dir(&dir).unwrap().count();
+    assert_eq!(frames, history.len() + 1);
+    let first = std::fs::read_to_string(dir.join("generation_0001.txt")).unwrap();
+    assert_eq!(first.matches('x').count(), 13);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_incremental_backend_matches_ping_pong() {
+    let mut ping_pong = example_solver();
+    let mut incremental = Day4Solver::default().with_backend(Backend::Incremental);
+    for line in EXAMPLE.lines() {
+        incremental.add_row(line).expect("Failed to add row");
+    }
+    incremental.finalize_input().expect("Empty grid");
+
+    loop {
+        let expected = ping_pong.solve().expect("Solver failed");
+        assert_eq!(incremental.solve().expect("Solver failed"), expected);
+        assert_eq!(incremental.removed_cells(), ping_pong.removed_cells());
+        assert_eq!(incremental.cells(), ping_pong.cells());
+        if expected == 0 {
+            break;
+        }
+    }
+}
+
+fn solver_from(grid: &[&str], solver: Day4Solver) -> Day4Solver {
+    let mut solver = solver;
+    for line in grid {
+        solver.add_row(line).expect("Failed to add row");
+    }
+    solver.finalize_input().expect("Empty grid");
+    solver
+}
+
+#[test]
+fn test_kernel_validation() {
+    assert!(Kernel::new(vec![]).is_err());
+    assert!(Kernel::new(vec![1; 4]).is_err()); // 2x2 is even
+    assert!(Kernel::new(vec![1; 8]).is_err()); // not square
+    assert!(Kernel::new(vec![u32::MAX, 1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(Day4Solver::new(vec![1; 10], 4).is_err());
+
+    let kernel = Kernel::new(vec![1; 25]).unwrap();
+    assert_eq!(kernel.size(), 5);
+    assert_eq!(kernel.offsets().len(), 25);
+    assert_eq!(kernel.offsets()[0], (-2, -2, 1));
+    assert_eq!(Kernel::new(vec![7]).unwrap().offsets(), &[(0, 0, 7)]);
+}
+
+#[test]
+fn test_weighted_5x5_kernel() {
+    // Only the cells two steps away count, with a weight larger than u8 allows
+    let mut weights = vec![0; 25];
+    weights[0] = 300;
+    weights[4] = 300;
+    let grid = ["@.@", "...", "@.@"];
+    let mut solver = solver_from(&grid, Day4Solver::new(weights, 300).unwrap());
+
+    // The bottom corners see each other's top corner, the top row sees nothing above it
+    solver.solve().unwrap();
+    assert_eq!(solver.removed_cells(), vec![(0, 0), (2, 0)]);
+}
+
+#[test]
+fn test_boundary_resolve_and_sources() {
+    assert_eq!(Boundary::Empty.resolve(-1, 5), None);
+    assert_eq!(Boundary::Wrap.resolve(-1, 5), Some(4));
+    assert_eq!(Boundary::Wrap.resolve(7, 5), Some(2));
+    assert_eq!(Boundary::Reflect.resolve(-1, 5), Some(0));
+    assert_eq!(Boundary::Reflect.resolve(-2, 5), Some(1));
+    assert_eq!(Boundary::Reflect.resolve(5, 5), Some(4));
+    assert_eq!(Boundary::Reflect.resolve(11, 5), Some(1));
+
+    // sources must be exactly the inverse of resolve
+    for boundary in [Boundary::Empty, Boundary::Wrap, Boundary::Reflect] {
+        for len in 1..6usize {
+            for d in -7..=7isize {
+                for i in 0..len {
+                    let mut expected: Vec<usize> = (0..len)
+                        .filter(|&t| boundary.resolve(t as isize + d, len) == Some(i))
+                        .collect();
+                    let mut actual: Vec<usize> = boundary.sources(i, d, len).collect();
+                    expected.sort();
+                    actual.sort();
+                    assert_eq!(
+                        actual, expected,
+                        "{:?} len {} d {} i {}",
+                        boundary, len, d, i
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_boundary_modes() {
+    // A full 3x3 block: with an empty boundary only the corners have fewer than 4 neighbours,
+    // on a torus every cell sees all 8 others and nothing is removed.
+    let grid = ["@@@", "@@@", "@@@"];
+
+    let mut empty = solver_from(&grid, Day4Solver::default());
+    assert_eq!(empty.solve().unwrap(), 4);
+
+    let mut wrap = solver_from(&grid, Day4Solver::default().with_boundary(Boundary::Wrap));
+    assert_eq!(wrap.solve().unwrap(), 0);
+
+    // Reflected, a corner sees itself three times plus its three neighbours
+    let grid = ["@.", ".."];
+    let mut reflect = solver_from(
+        &grid,
+        Day4Solver::default().with_boundary(Boundary::Reflect),
+    );
+    assert_eq!(reflect.solve().unwrap(), 1);
+    let mut reflect = solver_from(
+        &grid,
+        Day4Solver::new(vec![1, 1, 1, 1, 0, 1, 1, 1, 1], 3)
+            .unwrap()
+            .with_boundary(Boundary::Reflect),
+    );
+    assert_eq!(reflect.solve().unwrap(), 0);
+}
+
+#[test]
+fn test_incremental_matches_ping_pong_for_all_boundaries() {
+    let grid: Vec<&str> = EXAMPLE.lines().collect();
+    let mut weights = vec![1; 25];
+    weights[12] = 0;
+    weights[0] = 3;
+
+    for boundary in [Boundary::Empty, Boundary::Wrap, Boundary::Reflect] {
+        let build = |backend| {
+            solver_from(
+                &grid,
+                Day4Solver::new(weights.clone(), 18)
+                    .unwrap()
+                    .with_boundary(boundary)
+                    .with_backend(backend),
+            )
+        };
+        let mut ping_pong = build(Backend::PingPong);
+        let mut incremental = build(Backend::Incremental);
+
+        let expected = ping_pong.run_until_stable(None).unwrap();
+        assert!(!expected.is_empty());
+        assert_eq!(incremental.run_until_stable(None).unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_rule_parsing() {
+    assert_eq!(format!("{:?}", Rule::parse("B3/S23").unwrap()), "B3/S23");
+    assert_eq!(format!("{:?}", Rule::parse("b36/s23").unwrap()), "B36/S23");
+    assert_eq!(
+        format!("{:?}", Rule::parse("B/S45678").unwrap()),
+        "B/S45678"
+    );
+
+    let brain = Rule::parse("B2/S/C3").unwrap();
+    assert_eq!(brain.states(), 3);
+    assert_eq!(format!("{:?}", brain), "B2/S/C3");
+    assert_eq!(Rule::parse("B2/S/3").unwrap().states(), 3);
+
+    for invalid in [
+        "",
+        "B3S23",
+        "X3/S23",
+        "B9/S23",
+        "B3/S2a",
+        "B3/S23/C1",
+        "B3/S23/C4/1",
+    ] {
+        assert!(
+            Rule::parse(invalid).is_err(),
+            "{:?} should not parse",
+            invalid
+        );
+    }
+    assert!(Rule::conway().with_states(200).is_err());
+}
+
+#[test]
+fn test_day4_rule_presets_agree() {
+    for rule in [
+        Rule::threshold(4),
+        Rule::parse("B/S45678").unwrap(),
+        Rule::custom(|alive, count| alive && count >= 4),
+    ] {
+        let mut solver = example_solver().with_rule(rule);
+        let history = solver.run_until_stable(None).unwrap();
+        assert_eq!(history[0].count(), 13);
+        assert_eq!(history.iter().map(|g| g.count()).sum::<u64>(), 43);
+        assert!(history.iter().all(|g| g.born().is_empty()));
+    }
+}
+
+#[test]
+fn test_conway_blinker_and_block() {
+    let blinker = [".....", "..@..", "..@..", "..@..", "....."];
+    for backend in [Backend::PingPong, Backend::Incremental] {
+        let solver = Day4Solver::default()
+            .with_rule(Rule::conway())
+            .with_backend(backend);
+        let mut solver = solver_from(&blinker, solver);
+
+        // An oscillator never becomes stable
+        let history = solver.run_until_stable(Some(4)).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].removed(), &[(2, 1), (2, 3)]);
+        assert_eq!(history[0].born(), &[(1, 2), (3, 2)]);
+        assert_eq!(render_text(&solver).lines().nth(2), Some(".x@x."));
+    }
+
+    let block = ["....", ".@@.", ".@@.", "...."];
+    let mut solver = solver_from(&block, Day4Solver::default().with_rule(Rule::conway()));
+    assert!(solver.run_until_stable(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_glider_on_torus() {
+    let glider = [".@....", "..@...", "@@@...", "......", "......", "......"];
+    for backend in [Backend::PingPong, Backend::Incremental] {
+        let solver = Day4Solver::default()
+            .with_rule(Rule::conway())
+            .with_boundary(Boundary::Wrap)
+            .with_backend(backend);
+        let mut solver = solver_from(&glider, solver);
+
+        // After 24 generations the glider has moved 6 cells diagonally, i.e. all the way around
+        solver.run_until_stable(Some(24)).unwrap();
+        let alive: Vec<String> = render_text(&solver)
+            .lines()
+            .map(|l| l.replace('x', "."))
+            .collect();
+        assert_eq!(alive, glider);
+    }
+}
+
+#[test]
+fn test_generations_dying_states() {
+    let grid = ["...", ".@.", "..."];
+    let rule = Rule::parse("B2/S/C4").unwrap();
+    for backend in [Backend::PingPong, Backend::Incremental] {
+        let solver = Day4Solver::default()
+            .with_rule(rule.clone())
+            .with_backend(backend);
+        let mut solver = solver_from(&grid, solver);
+
+        solver.solve().unwrap();
+        assert_eq!(solver.cells()[4], -1);
+        solver.solve().unwrap();
+        assert_eq!(solver.cells()[4], -2);
+        solver.solve().unwrap();
+        assert_eq!(solver.cells()[4], 0);
+    }
+
+    let mut solver = solver_from(&grid, Day4Solver::default().with_rule(rule));
+    let history = solver.run_until_stable(None).unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].removed(), &[(1, 1)]);
+}
+
+#[test]
+fn test_incremental_matches_ping_pong_for_life_rules() {
+    let grid: Vec<&str> = EXAMPLE.lines().collect();
+    for rule in ["B3/S23", "B36/S23", "B2/S/C3", "B35678/S5678/C5"] {
+        for boundary in [Boundary::Empty, Boundary::Wrap, Boundary::Reflect] {
+            let build = |backend| {
+                let solver = Day4Solver::default()
+                    .with_rule(Rule::parse(rule).unwrap())
+                    .with_boundary(boundary)
+                    .with_backend(backend);
+                solver_from(&grid, solver)
+            };
+            let mut ping_pong = build(Backend::PingPong);
+            let mut incremental = build(Backend::Incremental);
+
+            for _ in 0..30 {
+                assert_eq!(incremental.solve().unwrap(), ping_pong.solve().unwrap());
+                assert_eq!(
+                    incremental.cells(),
+                    ping_pong.cells(),
+                    "{} {:?}",
+                    rule,
+                    boundary
+                );
+                assert_eq!(incremental.born_cells(), ping_pong.born_cells());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bitboard_matches_ping_pong() {
+    let mut solver = solver_from(
+        &EXAMPLE.lines().collect::<Vec<_>>(),
+        Day4Solver::default().with_backend(Backend::Bitboard),
+    );
+    let history = solver.run_until_stable(None).expect("Solver failed");
+    assert_eq!(history[0].count(), 13);
+    assert_eq!(history.iter().map(|g| g.count()).sum::<u64>(), 43);
+
+    // 130 columns span three words per row, so neighbours cross word boundaries
+    let mut rng = XorShift::default();
+    let wide: Vec<String> = (0..40)
+        .map(|_| {
+            (0..130)
+                .map(|_| if rng.below(100) < 45 { '@' } else { '.' })
+                .collect()
+        })
+        .collect();
+    let wide: Vec<&str> = wide.iter().map(|s| s.as_str()).collect();
+
+    for rule in [
+        Rule::default(),
+        Rule::conway(),
+        Rule::parse("B36/S23").unwrap(),
+    ] {
+        for grid in [EXAMPLE.lines().collect::<Vec<_>>(), wide.clone()] {
+            let build = |backend| {
+                let solver = Day4Solver::default()
+                    .with_rule(rule.clone())
+                    .with_backend(backend);
+                solver_from(&grid, solver)
+            };
+            let mut ping_pong = build(Backend::PingPong);
+            let mut bitboard = build(Backend::Bitboard);
+
+            for _ in 0..30 {
+                assert_eq!(bitboard.solve().unwrap(), ping_pong.solve().unwrap());
+                assert_eq!(bitboard.cells(), ping_pong.cells(), "{:?}", rule);
+                assert_eq!(bitboard.removed_cells(), ping_pong.removed_cells());
+                assert_eq!(bitboard.born_cells(), ping_pong.born_cells());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_backend_change_keeps_added_rows() {
+    let backends = [Backend::PingPong, Backend::Incremental, Backend::Bitboard];
+    for from in backends {
+        for to in backends {
+            let mut solver = Day4Solver::default().with_backend(from);
+            for line in EXAMPLE.lines() {
+                solver.add_row(line).unwrap();
+            }
+            let mut solver = solver.with_backend(to);
+            solver.finalize_input().expect("Empty grid");
+
+            assert_eq!(solver.cells().len(), 100, "{:?} -> {:?}", from, to);
+            let history = solver.run_until_stable(None).expect("Solver failed");
+            assert_eq!(history[0].count(), 13, "{:?} -> {:?}", from, to);
+            assert_eq!(history.iter().map(|g| g.count()).sum::<u64>(), 43);
+        }
+    }
+
+    // Switching after a few generations carries on from the latest one
+    let mut solver = example_solver();
+    let first = solver.run_until_stable(Some(2)).unwrap();
+    let mut solver = solver.with_backend(Backend::Bitboard);
+    solver.finalize_input().unwrap();
+    let rest = solver.run_until_stable(None).unwrap();
+    let total: u64 = first.iter().chain(&rest).map(|g| g.count()).sum();
+    assert_eq!(total, 43);
+}
+
+#[test]
+fn test_bitboard_rejects_unsupported_configurations() {
+    let grid: Vec<&str> = EXAMPLE.lines().collect();
+    let unsupported = [
+        Day4Solver::default().with_boundary(Boundary::Wrap),
+        Day4Solver::default().with_rule(Rule::parse("B2/S/C3").unwrap()),
+        Day4Solver::default().with_rule(Rule::custom(|alive, n| alive && n > 1)),
+        Day4Solver::with_kernel(Kernel::new(vec![1; 25]).unwrap(), 4),
+    ];
+    for solver in unsupported {
+        let mut solver = solver_from(&grid, solver.with_backend(Backend::Bitboard));
+        assert!(solver.solve().is_err());
+    }
+}
+
+#[test]
+fn test_ragged_rows_rejected() {
+    let mut solver = Day4Solver::default();
+    solver.add_row("@@.@").unwrap();
+    solver.add_row("").unwrap();
+
+    let err = solver.add_row("@@.").unwrap_err().to_string();
+    assert!(err.contains("Line 3"), "{}", err);
+    assert!(err.contains("3 cells") && err.contains("4 wide"), "{}", err);
+    assert!(solver.add_row("@@.@@").is_err());
+
+    let err = solver.add_row("@#.@").unwrap_err().to_string();
+    assert!(
+        err.contains("Line 5") && err.contains("column 2"),
+        "{}",
+        err
+    );
+
+    // Rejected rows are not added
+    solver.add_row(".@@.").unwrap();
+    solver.finalize_input().unwrap();
+    assert_eq!((solver.width(), solver.height()), (4, 2));
+}
+
+#[test]
+fn test_empty_grid_rejected() {
+    for backend in [Backend::PingPong, Backend::Incremental, Backend::Bitboard] {
+        let mut solver = Day4Solver::default().with_backend(backend);
+        solver.add_row("").unwrap();
+        assert!(matches!(
+            solver.finalize_input(),
+            Err(UpdateError::EmptyInput)
+        ));
+    }
+}
+
+#[test]
+fn test_many_generations() {
+    // A blinker oscillates forever, well past the range of a small counter
+    let grid = [".....", "..@..", "..@..", "..@..", "....."];
+    for backend in [Backend::PingPong, Backend::Incremental, Backend::Bitboard] {
+        let solver = Day4Solver::default()
+            .with_rule(Rule::conway())
+            .with_backend(backend);
+        let mut solver = solver_from(&grid, solver);
+
+        let history = solver.run_until_stable(Some(300)).expect("Solver failed");
+        assert_eq!(history.len(), 300);
+        assert_eq!(solver.generation(), 300);
+        assert_eq!(render_text(&solver).lines().nth(2), Some(".x@x."));
+    }
+}
+
+#[test]
+fn test_bands_match_in_memory_solver() {
+    let grid: Vec<&str> = EXAMPLE.lines().collect();
+    let configs = [
+        (Kernel::moore(), Rule::default(), Boundary::Empty),
+        (Kernel::moore(), Rule::default(), Boundary::Reflect),
+        (
+            Kernel::new(vec![1; 25]).unwrap(),
+            Rule::threshold(12),
+            Boundary::Reflect,
+        ),
+        (
+            Kernel::moore(),
+            Rule::parse("B2/S/C3").unwrap(),
+            Boundary::Empty,
+        ),
+    ];
+
+    for (kernel, rule, boundary) in configs {
+        let mut expected = solver_from(
+            &grid,
+            Day4Solver::with_kernel(kernel.clone(), 0)
+                .with_rule(rule.clone())
+                .with_boundary(boundary),
+        );
+        let history = expected.run_until_stable(Some(20)).expect("Solver failed");
+        let expected_counts: Vec<u64> = history.iter().map(|g| g.count()).collect();
+
+        for band_rows in [1, 2, 3, 4, 100] {
+            let bands = BandSolver::new()
+                .with_kernel(kernel.clone())
+                .with_rule(rule.clone())
+                .with_boundary(boundary)
+                .with_band_rows(band_rows);
+            let mut output = Vec::new();
+            let counts = bands
+                .run(EXAMPLE.as_bytes(), &mut output, Some(20))
+                .expect("Banded run failed");
+
+            let removed: Vec<u64> = counts.iter().map(|c| c.removed()).collect();
+            assert_eq!(removed, expected_counts, "{:?} {} rows", rule, band_rows);
+            assert_eq!(String::from_utf8(output).unwrap(), render_text(&expected));
+        }
+    }
+}
+
+#[test]
+fn test_bands_first_step() {
+    let mut output = Vec::new();
+    let counts = BandSolver::new()
+        .with_band_rows(3)
+        .run(EXAMPLE.as_bytes(), &mut output, Some(1))
+        .expect("Banded run failed");
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[0].removed(), 13);
+
+    // Without any generation the input is written back unchanged
+    let mut output = Vec::new();
+    BandSolver::new()
+        .run(EXAMPLE.as_bytes(), &mut output, Some(0))
+        .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap().trim_end(), EXAMPLE);
+}
+
+#[test]
+fn test_bands_invalid_input() {
+    let bands = BandSolver::new();
+    assert!(matches!(
+        bands.run("\n\n".as_bytes(), io::sink(), None),
+        Err(UpdateError::EmptyInput)
+    ));
+
+    let err = bands
+        .run("@@.\n@.\n".as_bytes(), io::sink(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Line 2"), "{}", err);
+
+    let wrap = BandSolver::new().with_boundary(Boundary::Wrap);
+    assert!(wrap.run(EXAMPLE.as_bytes(), io::sink(), None).is_err());
+}
@@ -0,0 +1,276 @@
+use advent_of_code_2025::adv_errors::UpdateError;
+use advent_of_code_2025::day5::audit::{Auditor, audit_ingredients};
+use advent_of_code_2025::day5::interval_set::IntervalSet;
+use advent_of_code_2025::day5::{self, InputReader, MergeMode, Range};
+use advent_of_code_2025::rng::XorShift;
+
+const EXAMPLE: &str = "\
+3-5
+10-14
+16-20
+12-18
+
+1
+5
+8
+11
+17
+32
+";
+
+fn set(ranges: &[(i64, i64)]) -> IntervalSet {
+    ranges.iter().map(|&(a, b)| Range::new(a, b)).collect()
+}
+
+#[test]
+fn test_case_example() {
+    let (fresh, total) = day5::solve(EXAMPLE.as_bytes()).expect("Solver failed");
+
+    assert_eq!(fresh, 3);
+    assert_eq!(total, 14);
+}
+
+#[test]
+fn test_interval_set_contains() {
+    let fresh = set(&[(3, 5), (10, 14), (16, 20), (12, 18)]);
+
+    assert_eq!(fresh.ranges(), &[Range::new(3, 5), Range::new(10, 20)]);
+    assert_eq!(fresh.coverage(), Some(14));
+    let hits: Vec<i64> = (0..25).filter(|&v| fresh.contains(v)).collect();
+    let expected: Vec<i64> = (3..=5).chain(10..=20).collect();
+    assert_eq!(hits, expected);
+
+    assert!(!IntervalSet::default().contains(0));
+    assert!(set(&[(i64::MIN, i64::MIN)]).contains(i64::MIN));
+}
+
+#[test]
+fn test_interval_set_operations() {
+    let a = set(&[(1, 5), (10, 20)]);
+    let b = set(&[(4, 12), (18, 25)]);
+
+    assert_eq!(a.union(&b), set(&[(1, 25)]));
+    assert_eq!(a.intersection(&b), set(&[(4, 5), (10, 12), (18, 20)]));
+    assert_eq!(a.difference(&b), set(&[(1, 3), (13, 17)]));
+    assert_eq!(b.difference(&a), set(&[(6, 9), (21, 25)]));
+    assert_eq!(
+        a.complement(Range::new(0, 30)),
+        set(&[(0, 0), (6, 9), (21, 30)])
+    );
+
+    // No overflow at the edges of the value range
+    let all = set(&[(i64::MIN, i64::MAX)]);
+    assert!(all.complement(Range::new(i64::MIN, i64::MAX)).is_empty());
+    assert_eq!(
+        set(&[(0, 0)]).complement(Range::new(i64::MIN, i64::MAX)),
+        set(&[(i64::MIN, -1), (1, i64::MAX)])
+    );
+}
+
+#[test]
+fn test_interval_set_operations_match_brute_force() {
+    let mut rng = XorShift::default();
+    let mut next = |n: u64| rng.below(n) as i64;
+
+    for _ in 0..200 {
+        let mut random_set = || -> IntervalSet {
+            (0..next(5))
+                .map(|_| {
+                    let lower = next(40);
+                    Range::new(lower, lower + next(8))
+                })
+                .collect()
+        };
+        let (a, b) = (random_set(), random_set());
+        let bounds = Range::new(5, 35);
+
+        for v in -2..50 {
+            let (in_a, in_b) = (a.contains(v), b.contains(v));
+            assert_eq!(a.union(&b).contains(v), in_a || in_b);
+            assert_eq!(a.intersection(&b).contains(v), in_a && in_b);
+            assert_eq!(a.difference(&b).contains(v), in_a && !in_b);
+            assert_eq!(
+                a.complement(bounds).contains(v),
+                bounds.contains(v) && !in_a
+            );
+        }
+        let in_range = (-2..50).filter(|&v| a.contains(v)).count() as u128;
+        assert_eq!(a.coverage(), Some(in_range));
+    }
+}
+
+#[test]
+fn test_audit_ingredients() {
+    let (ranges, ingredients) =
+        day5::read_ranges_ingredients(EXAMPLE.as_bytes()).expect("Failed to parse");
+    let records = audit_ingredients(&ranges, &ingredients);
+
+    let fresh: Vec<i64> = records
+        .iter()
+        .filter(|r| r.is_fresh())
+        .map(|r| r.ingredient())
+        .collect();
+    assert_eq!(fresh, vec![5, 11, 17]);
+
+    // 17 lies in two overlapping input ranges, which merge into 10-20
+    let record = &records[4];
+    assert_eq!(
+        record.sources(),
+        &[(2, Range::new(16, 20)), (3, Range::new(12, 18))]
+    );
+    assert_eq!(record.merged_index(), Some(1));
+    assert_eq!(
+        record.to_string(),
+        "17: fresh via 16-20 (#3), 12-18 (#4) in merged range #2"
+    );
+
+    let spoiled = &records[0];
+    assert!(spoiled.sources().is_empty());
+    assert_eq!(spoiled.merged_index(), None);
+    assert_eq!(spoiled.to_string(), "1: spoiled");
+}
+
+#[test]
+fn test_lenient_input() {
+    let input = "# fresh ranges\r\n -5--2 \r\n 3 - 7\r\n+10-12 # inline comment\r\n# still ranges\r\n\r\n -3\r\n\t8 \r\n\r\n11\r\n";
+    let (ranges, ingredients) =
+        day5::read_ranges_ingredients(input.as_bytes()).expect("Failed to parse");
+
+    assert_eq!(
+        ranges,
+        vec![Range::new(-5, -2), Range::new(3, 7), Range::new(10, 12)]
+    );
+    assert_eq!(ingredients, vec![-3, 8, 11]);
+    assert_eq!(day5::solve(input.as_bytes()).unwrap(), (2, 12));
+}
+
+#[test]
+fn test_ranges_only_input() {
+    let (ranges, ingredients) = day5::read_ranges_ingredients::<i64, _>("3-5\n10-14\n".as_bytes())
+        .expect("Failed to parse");
+
+    assert_eq!(ranges.len(), 2);
+    assert!(ingredients.is_empty());
+    assert_eq!(day5::solve("3-5\n10-14".as_bytes()).unwrap(), (0, 8));
+}
+
+#[test]
+fn test_invalid_input() {
+    let error = |input: &str| {
+        day5::read_ranges_ingredients::<i64, _>(input.as_bytes())
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert!(error("3-5\n7\n").contains("Line 2: \"7\" is not a range"));
+    assert!(error("3-5\n4-x\n").contains("Line 2: \"x\" could not be parsed"));
+    assert!(error("3-5\n\n1\n2 3\n").contains("Line 4: \"2 3\" could not be parsed"));
+    assert!(matches!(
+        day5::read_ranges_ingredients::<i64, _>("# nothing\n\n".as_bytes()),
+        Err(UpdateError::EmptyInput)
+    ));
+}
+
+#[test]
+fn test_wide_bounds() {
+    // Spans near the ends of the value range do not overflow
+    assert_eq!(Range::new(i64::MIN, i64::MAX).span(), Some(1 << 64));
+    assert_eq!(Range::new(0, u64::MAX).span(), Some(1 << 64));
+    assert_eq!(Range::new(i128::MIN, i128::MAX).span(), None);
+    assert_eq!(Range::new(i128::MIN, i128::MAX - 1).span(), Some(u128::MAX));
+
+    let set: IntervalSet<u64> = [Range::new(0, u64::MAX - 1), Range::new(5, u64::MAX)]
+        .into_iter()
+        .collect();
+    assert_eq!(set.coverage(), Some(1 << 64));
+    assert!(set.contains(u64::MAX));
+    assert!(set.complement(Range::new(0, u64::MAX)).is_empty());
+
+    let halves: IntervalSet<i128> = [Range::new(i128::MIN, -1), Range::new(0, i128::MAX)]
+        .into_iter()
+        .collect();
+    assert_eq!(halves.coverage(), None);
+
+    // Unsigned IDs beyond i64 are read as they are
+    let input = "18446744073709551000-18446744073709551615\n\n18446744073709551615\n";
+    let (ranges, ingredients) =
+        day5::read_ranges_ingredients::<u64, _>(input.as_bytes()).expect("Failed to parse");
+    assert_eq!(ranges, vec![Range::new(u64::MAX - 615, u64::MAX)]);
+    assert_eq!(ingredients, vec![u64::MAX]);
+    assert_eq!(day5::solve(input.as_bytes()).unwrap(), (1, 616));
+
+    let err = day5::read_ranges_ingredients::<u64, _>("-3-5\n".as_bytes()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("\"-3\" could not be parsed as u64")
+    );
+}
+
+#[test]
+fn test_merge_adjacent_ranges() {
+    let ranges = vec![
+        Range::new(6, 8),
+        Range::new(3, 5),
+        Range::new(10, 14),
+        Range::new(12, 18),
+        Range::new(25, 30),
+    ];
+
+    let (merged, stats) = day5::merge_ranges_with(ranges.clone(), MergeMode::Overlapping);
+    assert_eq!(merged, day5::merge_ranges(ranges.clone()));
+    assert_eq!(merged.len(), 4);
+    assert_eq!((stats.input_count(), stats.merged_count()), (5, 4));
+    assert_eq!((stats.overlaps(), stats.adjacent()), (1, 0));
+    assert_eq!(stats.largest_gap(), Some(Range::new(19, 24)));
+
+    let (merged, stats) = day5::merge_ranges_with(ranges, MergeMode::Adjacent);
+    assert_eq!(
+        merged,
+        vec![Range::new(3, 8), Range::new(10, 18), Range::new(25, 30)]
+    );
+    assert_eq!((stats.input_count(), stats.merged_count()), (5, 3));
+    assert_eq!((stats.overlaps(), stats.adjacent()), (1, 1));
+    assert_eq!(stats.largest_gap(), Some(Range::new(19, 24)));
+
+    // Touching ranges at the end of the value range, and no gaps at all
+    let (merged, stats) = day5::merge_ranges_with(
+        vec![Range::new(0, 9), Range::new(10, u64::MAX)],
+        MergeMode::Adjacent,
+    );
+    assert_eq!(merged, vec![Range::new(0, u64::MAX)]);
+    assert_eq!(stats.largest_gap(), None);
+}
+
+#[test]
+fn test_streaming_ingredients() {
+    let mut input = InputReader::new(EXAMPLE.as_bytes());
+    let ranges = input.read_ranges::<i64>().expect("Failed to parse ranges");
+    assert_eq!(ranges.len(), 4);
+
+    let auditor = Auditor::new(&ranges);
+    let first = input.next_ingredient().unwrap().unwrap();
+    assert_eq!(first, 1);
+    assert!(!auditor.audit(first).is_fresh());
+    let rest: Vec<i64> = input.ingredients().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rest, vec![5, 8, 11, 17, 32]);
+    assert_eq!(input.next_ingredient::<i64>().unwrap(), None);
+
+    // Batches of any size agree with the sequential count
+    let ingredients: String = (0..1000).map(|i| format!("{}\n", i * 7 % 31)).collect();
+    let input = format!("3-5\n10-14\n16-20\n12-18\n\n{}", ingredients);
+    let expected = day5::solve(input.as_bytes()).unwrap();
+    for batch_size in [None, Some(0), Some(1), Some(7), Some(4096)] {
+        let mut reader = InputReader::new(input.as_bytes());
+        let ranges = reader.read_ranges::<i64>().unwrap();
+        let result = day5::solve_stream(ranges, &mut reader, batch_size).unwrap();
+        assert_eq!(result, expected, "{:?}", batch_size);
+    }
+}
+
+#[test]
+fn test_streaming_errors_are_located() {
+    let mut input = InputReader::new("3-5\n\n4\nx\n".as_bytes());
+    let ranges = input.read_ranges::<i64>().unwrap();
+    let err = day5::solve_stream(ranges, &mut input, Some(16)).unwrap_err();
+    assert!(err.to_string().contains("Line 4"), "{}", err);
+}
@@ -0,0 +1,336 @@
+use advent_of_code_2025::adv_errors::UpdateError;
+use advent_of_code_2025::day6;
+use advent_of_code_2025::day6::generator::{MAX_DIGITS, WorksheetGenerator};
+use advent_of_code_2025::day6::worksheet::{Axis, Direction, Op, ReadingOrder, Worksheet};
+use advent_of_code_2025::rng::XorShift;
+
+// Built line by line so the significant trailing spaces survive editors
+const EXAMPLE: &str = concat!(
+    "123 328  51 64 \n",
+    " 45 64  387 23 \n",
+    "  6 98  215 314\n",
+    "*   +   *   +  \n",
+);
+
+#[test]
+fn test_case_example() {
+    let (part1, part2) = day6::solve(EXAMPLE.as_bytes()).expect("Solver failed");
+
+    assert_eq!(part1, 4277556);
+    assert_eq!(part2, 3263827);
+}
+
+#[test]
+fn test_worksheet_problems() {
+    let worksheet = Worksheet::parse(EXAMPLE.as_bytes()).expect("Failed to parse");
+    let problems = worksheet.problems();
+    assert_eq!(problems.len(), 4);
+
+    let spans: Vec<_> = problems.iter().map(|p| p.span()).collect();
+    assert_eq!(spans, vec![0..3, 4..7, 8..11, 12..15]);
+    let ops: Vec<Op> = problems.iter().map(|p| p.op()).collect();
+    assert_eq!(ops, vec![Op::Mul, Op::Add, Op::Mul, Op::Add]);
+
+    assert_eq!(problems[0].row_operands(), &[123, 45, 6]);
+    assert_eq!(problems[0].column_operands(), &[1, 24, 356]);
+    assert_eq!(problems[0].row_result().unwrap(), 33210);
+    assert_eq!(problems[0].column_result().unwrap(), 8544);
+
+    assert_eq!(problems[3].row_operands(), &[64, 23, 314]);
+    assert_eq!(problems[3].column_operands(), &[623, 431, 4]);
+    assert_eq!(problems[3].row_result().unwrap(), 401);
+    assert_eq!(problems[3].column_result().unwrap(), 1058);
+
+    let row_results: Vec<u64> = problems.iter().map(|p| p.row_result().unwrap()).collect();
+    assert_eq!(row_results, vec![33210, 490, 4243455, 401]);
+    let column_results: Vec<u64> = problems
+        .iter()
+        .map(|p| p.column_result().unwrap())
+        .collect();
+    assert_eq!(column_results, vec![8544, 625, 3253600, 1058]);
+    assert_eq!(worksheet.row_total().unwrap(), 4277556);
+    assert_eq!(worksheet.column_total().unwrap(), 3263827);
+}
+
+#[test]
+fn test_more_operators() {
+    let input = concat!(
+        "100 12  7   9  \n",
+        " 30 3   21  40 \n",
+        "  5 2   3   5  \n",
+        "-   /   max min\n",
+    );
+    let worksheet = Worksheet::parse(input.as_bytes()).expect("Failed to parse");
+    let ops: Vec<Op> = worksheet.problems().iter().map(|p| p.op()).collect();
+    assert_eq!(ops, vec![Op::Sub, Op::Div, Op::Max, Op::Min]);
+
+    // Non-commutative operators apply in reading order: 100 - 30 - 5 and 12 / 3 / 2
+    let rows: Vec<u64> = worksheet
+        .problems()
+        .iter()
+        .map(|p| p.row_result().unwrap())
+        .collect();
+    assert_eq!(rows, vec![65, 2, 21, 5]);
+
+    // Columns read top-to-bottom, left to right: 1 - 3 - 5 underflows
+    let err = worksheet.problems()[0].column_result().unwrap_err();
+    assert!(err.to_string().contains("columns 1-3"), "{}", err);
+    assert_eq!(worksheet.problems()[1].column_operands(), &[132, 2]);
+    assert_eq!(worksheet.problems()[1].column_result().unwrap(), 66);
+    // The last column of `max` holds no digits
+    assert_eq!(worksheet.problems()[2].column_operands(), &[723, 1]);
+    assert_eq!(worksheet.problems()[2].column_result().unwrap(), 723);
+}
+
+#[test]
+fn test_overflow_detection() {
+    let input = concat!(
+        "18446744073709551615 2 \n",
+        "18446744073709551615 3 \n",
+        "*                    + \n",
+    );
+    let worksheet = Worksheet::parse(input.as_bytes()).expect("Failed to parse");
+
+    let err = worksheet.problems()[0].row_result().unwrap_err();
+    assert!(err.to_string().contains("columns 1-20"), "{}", err);
+    assert!(err.to_string().contains("overflows u64"), "{}", err);
+    assert!(worksheet.row_total().is_err());
+
+    // Wider results hold the product
+    let expected = u64::MAX as u128 * u64::MAX as u128;
+    assert_eq!(
+        worksheet.problems()[0].row_result_as::<u128>().unwrap(),
+        expected
+    );
+    assert_eq!(worksheet.row_total_as::<u128>().unwrap(), expected + 5);
+    assert!(day6::solve(input.as_bytes()).is_err());
+    assert_eq!(
+        day6::solve_as::<u128, _>(input.as_bytes()).unwrap().0,
+        expected + 5
+    );
+}
+
+#[test]
+fn test_division_by_zero() {
+    let worksheet = Worksheet::parse("8 \n0 \n/ \n".as_bytes()).expect("Failed to parse");
+    let err = worksheet.problems()[0].row_result().unwrap_err();
+    assert!(err.to_string().contains("Division by zero"), "{}", err);
+}
+
+#[test]
+fn test_ragged_lines_and_tabs() {
+    // Trailing blanks are trimmed, CRLF line endings and blank lines are ignored
+    let trimmed: String = EXAMPLE
+        .lines()
+        .map(|l| format!("{}\r\n\r\n", l.trim_end()))
+        .collect();
+    let worksheet = Worksheet::parse(trimmed.as_bytes()).expect("Failed to parse");
+    assert_eq!(worksheet, Worksheet::parse(EXAMPLE.as_bytes()).unwrap());
+
+    // A short line simply has no operand in the problems it does not reach
+    let worksheet = Worksheet::parse("12 3\n4\n+  *\n".as_bytes()).expect("Failed to parse");
+    assert_eq!(worksheet.problems()[1].row_operands(), &[3]);
+    assert_eq!(worksheet.row_total().unwrap(), 16 + 3);
+
+    // Tabs expand to multiples of TAB_WIDTH, several blank columns form one separator
+    let tabbed = "12\t34    5\n6\t7\n*\t+     +\n";
+    let worksheet = Worksheet::parse(tabbed.as_bytes()).expect("Failed to parse");
+    let spans: Vec<_> = worksheet.problems().iter().map(|p| p.span()).collect();
+    assert_eq!(spans, vec![0..2, 8..10, 14..15]);
+    assert_eq!(worksheet.problems()[0].row_operands(), &[12, 6]);
+    assert_eq!(worksheet.problems()[1].row_operands(), &[34, 7]);
+    assert_eq!(worksheet.problems()[1].column_operands(), &[37, 4]);
+    assert_eq!(worksheet.problems()[2].row_operands(), &[5]);
+}
+
+#[test]
+fn test_segmentation_errors() {
+    let error = |input: &str| Worksheet::parse(input.as_bytes()).unwrap_err().to_string();
+
+    assert!(error("12 34\n+    \n").contains("columns 4-5: Expected exactly one operator"));
+    assert!(error("123\n+ *\n").contains("Line 2, columns 1-3: Expected exactly one operator"));
+    assert!(error("1x 3\n+  *\n").contains("Line 1, column 2: 'x' is not a digit"));
+    assert!(error("12\n%\n").contains("\"%\" is not a valid operator"));
+    assert!(matches!(
+        Worksheet::parse(" \n\n".as_bytes()),
+        Err(UpdateError::EmptyInput)
+    ));
+}
+
+#[test]
+fn test_reading_orders() {
+    let worksheet = Worksheet::parse("12\n34\n- \n".as_bytes()).expect("Failed to parse");
+    let problem = &worksheet.problems()[0];
+
+    let operands = |order| problem.operands(order).unwrap();
+    assert_eq!(operands(ReadingOrder::ROWS_LEFT_TO_RIGHT), vec![12, 34]);
+    assert_eq!(operands(ReadingOrder::ROWS_BOTTOM_TO_TOP), vec![34, 12]);
+    assert_eq!(operands(ReadingOrder::COLUMNS_TOP_TO_BOTTOM), vec![13, 24]);
+    assert_eq!(operands(ReadingOrder::COLUMNS_RIGHT_TO_LEFT), vec![24, 13]);
+    let mirrored = ReadingOrder::new(Axis::Rows, Direction::Backward, Direction::Forward);
+    assert_eq!(operands(mirrored), vec![21, 43]);
+
+    // The order matters for non-commutative operators
+    assert!(
+        problem
+            .result_as::<u64>(ReadingOrder::ROWS_LEFT_TO_RIGHT)
+            .is_err()
+    );
+    assert_eq!(
+        problem
+            .result_as::<u64>(ReadingOrder::ROWS_BOTTOM_TO_TOP)
+            .unwrap(),
+        22
+    );
+    assert_eq!(
+        problem
+            .result_as::<u64>(ReadingOrder::COLUMNS_RIGHT_TO_LEFT)
+            .unwrap(),
+        11
+    );
+
+    // The presets agree with the part 1 and part 2 readings
+    let worksheet = Worksheet::parse(EXAMPLE.as_bytes()).unwrap();
+    let total = |order| worksheet.total_as::<u64>(order).unwrap();
+    assert_eq!(total(ReadingOrder::ROWS_LEFT_TO_RIGHT), 4277556);
+    assert_eq!(total(ReadingOrder::COLUMNS_TOP_TO_BOTTOM), 3263827);
+    assert_eq!(total(ReadingOrder::COLUMNS_RIGHT_TO_LEFT), 3263827);
+    assert_eq!(
+        worksheet.problems()[3]
+            .operands(ReadingOrder::COLUMNS_RIGHT_TO_LEFT)
+            .unwrap(),
+        vec![4, 431, 623]
+    );
+}
+
+/// Lays `operands` out as a problem read back in `order`, returning its lines.
+fn layout(operands: &[u64], order: ReadingOrder) -> Vec<String> {
+    let mut numbers: Vec<Vec<char>> = operands
+        .iter()
+        .map(|n| {
+            let mut digits: Vec<char> = n.to_string().chars().collect();
+            if order.digits() == Direction::Backward {
+                digits.reverse();
+            }
+            digits
+        })
+        .collect();
+    if order.numbers() == Direction::Backward {
+        numbers.reverse();
+    }
+
+    let longest = numbers.iter().map(Vec::len).max().unwrap_or(0);
+    let pad = |digits: &[char], i: usize| digits.get(i).copied().unwrap_or(' ');
+    match order.axis() {
+        Axis::Rows => numbers
+            .iter()
+            .map(|digits| (0..longest).map(|i| pad(digits, i)).collect())
+            .collect(),
+        Axis::Columns => (0..longest)
+            .map(|i| numbers.iter().map(|digits| pad(digits, i)).collect())
+            .collect(),
+    }
+}
+
+#[test]
+fn test_reading_orders_round_trip() {
+    let mut rng = XorShift::new(0x9E37_79B9_7F4A_7C15);
+    let mut next = |n: u64| rng.below(n);
+
+    let orders = [
+        ReadingOrder::ROWS_LEFT_TO_RIGHT,
+        ReadingOrder::ROWS_BOTTOM_TO_TOP,
+        ReadingOrder::COLUMNS_TOP_TO_BOTTOM,
+        ReadingOrder::COLUMNS_RIGHT_TO_LEFT,
+    ];
+    for order in orders {
+        for _ in 0..50 {
+            let problems: Vec<Vec<u64>> = (0..1 + next(5))
+                .map(|_| {
+                    (0..1 + next(5))
+                        .map(|_| {
+                            let digits = 1 + next(6) as u32;
+                            next(10u64.pow(digits))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            // Problems side by side, one blank column apart, operators on the last line
+            let blocks: Vec<Vec<String>> = problems.iter().map(|p| layout(p, order)).collect();
+            let height = blocks.iter().map(Vec::len).max().unwrap();
+            let mut lines = vec![String::new(); height + 1];
+            for block in &blocks {
+                let width = block[0].len();
+                for (y, line) in lines.iter_mut().enumerate().take(height) {
+                    let text = block.get(y).map_or("", String::as_str);
+                    line.push_str(&format!("{:<width$} ", text, width = width));
+                }
+                lines[height].push_str(&format!("{:<width$} ", "+", width = width));
+            }
+            let input: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+
+            let worksheet = Worksheet::parse(input.as_bytes()).expect("Failed to parse");
+            assert_eq!(worksheet.problems().len(), problems.len(), "{}", input);
+            for (problem, expected) in worksheet.problems().iter().zip(&problems) {
+                assert_eq!(&problem.operands(order).unwrap(), expected, "{}", input);
+            }
+            let sum: u64 = problems.iter().flatten().sum();
+            assert_eq!(worksheet.total_as::<u64>(order).unwrap(), sum);
+        }
+    }
+}
+
+#[test]
+fn test_generated_worksheets() {
+    let generator = WorksheetGenerator::new()
+        .with_problems(200)
+        .with_heights(1..=6)
+        .with_digits(1..=5);
+    for seed in 1..=20 {
+        let generated = generator.clone().with_seed(seed).generate();
+        let input = generated.text().as_bytes();
+
+        let expected = (generated.part1().unwrap(), generated.part2().unwrap());
+        assert_eq!(day6::solve_as::<u128, _>(input).unwrap(), expected);
+        let worksheet = Worksheet::parse(input).unwrap();
+        assert_eq!(worksheet.problems().len(), 200);
+    }
+
+    // An empty worksheet is not valid input, so there is always one problem
+    let generated = WorksheetGenerator::new().with_problems(0).generate();
+    let expected = (generated.part1().unwrap(), generated.part2().unwrap());
+    assert_eq!(
+        day6::solve_as::<u128, _>(generated.text().as_bytes()).unwrap(),
+        expected
+    );
+    assert_eq!(
+        Worksheet::parse(generated.text().as_bytes())
+            .unwrap()
+            .problems()
+            .len(),
+        1
+    );
+
+    // Every operator, with answers that may not exist or only fit the wider type
+    let generator = WorksheetGenerator::new()
+        .with_problems(4)
+        .with_heights(1..=2)
+        .with_digits(1..=MAX_DIGITS)
+        .with_ops(vec![Op::Add, Op::Mul, Op::Sub, Op::Div, Op::Max, Op::Min]);
+    let mut outcomes = [0; 3];
+    for seed in 1..=200 {
+        let generated = generator.clone().with_seed(seed).generate();
+        let input = generated.text().as_bytes();
+
+        let worksheet = Worksheet::parse(input).unwrap();
+        let part1 = worksheet.row_total_as::<u128>().ok();
+        let part2 = worksheet.column_total_as::<u128>().ok();
+        assert_eq!((part1, part2), (generated.part1(), generated.part2()));
+        let fits = |part: Option<u128>| part.is_some_and(|p| p <= u64::MAX as u128);
+        assert_eq!(worksheet.row_total().is_ok(), fits(part1));
+        assert_eq!(worksheet.column_total().is_ok(), fits(part2));
+        outcomes[part1.is_some() as usize + fits(part1) as usize] += 1;
+    }
+    assert!(outcomes.iter().all(|&n| n > 0), "{:?}", outcomes);
+}
@@ -0,0 +1,94 @@
+use advent_of_code_2025::day7::{self, render, trace::Trace};
+
+const EXAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+#[test]
+fn test_case_example() {
+    let (splits, worlds) = day7::solve(EXAMPLE.as_bytes()).expect("Solver failed");
+
+    assert_eq!(splits, 21);
+    assert_eq!(worlds, 40);
+}
+
+#[test]
+fn test_trace_beams() {
+    let trace = Trace::record(EXAMPLE.as_bytes()).expect("Failed to trace");
+
+    assert_eq!((trace.width(), trace.height()), (15, 16));
+    assert_eq!((trace.splits(), trace.worlds()), (21, 40));
+    assert_eq!(trace.beams().len(), 16);
+    assert_eq!(trace.beams()[0][7], 1);
+    assert_eq!(trace.beams()[2], {
+        let mut beam = vec![0; 15];
+        beam[6] = 1;
+        beam[8] = 1;
+        beam
+    });
+
+    // Timelines meet below the middle splitter of the third row of splitters
+    assert_eq!(trace.hits(6, 7), Some(2));
+    assert_eq!(trace.hits(6, 6), None);
+    assert_eq!(trace.hits(0, 7), None);
+    // The splitter no beam reaches
+    assert_eq!(trace.hits(14, 9), Some(0));
+}
+
+#[test]
+fn test_render_text() {
+    let trace = Trace::record(EXAMPLE.as_bytes()).unwrap();
+    let text = render::render_text(&trace, false);
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], ".......S.......");
+    assert_eq!(lines[1], ".......|.......");
+    assert_eq!(lines[2], "......|^|......");
+    assert_eq!(lines[14], "|^|^|^|^|^|||^|");
+    assert_eq!(lines[15], "|.|.|.|.|.|||.|");
+
+    let annotated = render::render_text(&trace, true);
+    let lines: Vec<&str> = annotated.lines().collect();
+    assert_eq!(lines[1], ".......|.......");
+    assert_eq!(lines[6], "....|^|^|^|....  6:1  8:2  10:1");
+    assert!(lines[14].ends_with("8:7  10:0  14:1"));
+}
+
+#[test]
+fn test_render_svg() {
+    let trace = Trace::record(EXAMPLE.as_bytes()).unwrap();
+    let svg = render::render_svg(&trace, true);
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<polygon").count(), 22);
+    assert_eq!(svg.matches("<circle").count(), 1);
+    assert!(svg.contains(">7</text>"));
+    assert_eq!(
+        render::render_svg(&trace, false).matches("<text").count(),
+        0
+    );
+}
+
+#[test]
+fn test_invalid_input() {
+    assert!(Trace::record("".as_bytes()).is_err());
+    assert!(Trace::record("..S..\n..^\n".as_bytes()).is_err());
+    assert!(day7::solve("..S..\n..^\n".as_bytes()).is_err());
+    assert!(Trace::record("..^..\n".as_bytes()).is_err());
+}